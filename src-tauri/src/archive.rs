@@ -0,0 +1,184 @@
+use serde::Serialize;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Component, Path};
+
+// Largest entry read_archive_entry will decompress
+const MAX_ENTRY_BYTES: u64 = 50 * 1024 * 1024;
+
+#[derive(Debug, Serialize)]
+pub struct ArchiveEntry {
+    pub path: String,
+    pub size: u64,
+    pub is_dir: bool,
+}
+
+#[derive(Debug, PartialEq)]
+enum ArchiveFormat {
+    Tar,
+    TarGz,
+    Zip,
+}
+
+// Lists the entries of a tar or tar.gz archive. Zip archives are recognised
+// but not supported, and fail with an "Unsupported:" error.
+#[tauri::command]
+pub async fn list_archive(path: String) -> Result<Vec<ArchiveEntry>, String> {
+    let mut archive = open_tar(Path::new(&path))?;
+    let mut entries = Vec::new();
+
+    for entry in archive.entries().map_err(|e| e.to_string())? {
+        let entry = entry.map_err(|e| e.to_string())?;
+        let entry_path = entry.path().map_err(|e| e.to_string())?.to_path_buf();
+
+        // Names that would escape an extraction directory are never listed
+        if !is_safe_entry_path(&entry_path) {
+            continue;
+        }
+
+        entries.push(ArchiveEntry {
+            path: entry_path.to_string_lossy().to_string(),
+            size: entry.header().size().unwrap_or(0),
+            is_dir: entry.header().entry_type().is_dir(),
+        });
+    }
+
+    Ok(entries)
+}
+
+#[tauri::command]
+pub async fn read_archive_entry(path: String, entry_path: String) -> Result<String, String> {
+    if !is_safe_entry_path(Path::new(&entry_path)) {
+        return Err(format!("Invalid entry path: {}", entry_path));
+    }
+
+    let mut archive = open_tar(Path::new(&path))?;
+    for entry in archive.entries().map_err(|e| e.to_string())? {
+        let entry = entry.map_err(|e| e.to_string())?;
+        if entry.path().map_err(|e| e.to_string())? != Path::new(&entry_path) {
+            continue;
+        }
+
+        if entry.header().size().unwrap_or(0) > MAX_ENTRY_BYTES {
+            return Err(format!("TooLarge: {} is larger than {} bytes", entry_path, MAX_ENTRY_BYTES));
+        }
+
+        // The header size can't be trusted, so cap what is actually read too
+        let mut bytes = Vec::new();
+        entry.take(MAX_ENTRY_BYTES + 1).read_to_end(&mut bytes).map_err(|e| e.to_string())?;
+        if bytes.len() as u64 > MAX_ENTRY_BYTES {
+            return Err(format!("TooLarge: {} is larger than {} bytes", entry_path, MAX_ENTRY_BYTES));
+        }
+
+        return String::from_utf8(bytes).map_err(|_| format!("{} is not a text file", entry_path));
+    }
+
+    Err(format!("{} not found in {}", entry_path, path))
+}
+
+fn open_tar(path: &Path) -> Result<tar::Archive<Box<dyn Read>>, String> {
+    let mut file = File::open(path).map_err(|e| e.to_string())?;
+    let format = detect_format(&mut file).map_err(|e| e.to_string())?;
+
+    let reader: Box<dyn Read> = match format {
+        Some(ArchiveFormat::Tar) => Box::new(file),
+        Some(ArchiveFormat::TarGz) => Box::new(flate2::read::GzDecoder::new(file)),
+        Some(ArchiveFormat::Zip) => {
+            return Err(format!("Unsupported: {} is a zip archive; only tar and tar.gz can be read", path.display()))
+        }
+        None => return Err(format!("Unsupported archive format: {}", path.display())),
+    };
+    Ok(tar::Archive::new(reader))
+}
+
+// Detects the format from magic bytes rather than the extension, leaving the
+// reader positioned at the start. Old-style (v7) tars have no "ustar" magic,
+// so a first header with a valid checksum is taken as tar too.
+fn detect_format<R: Read + Seek>(file: &mut R) -> Result<Option<ArchiveFormat>, io::Error> {
+    let mut header = [0u8; 512];
+    let mut filled = 0;
+    while filled < header.len() {
+        match file.read(&mut header[filled..])? {
+            0 => break,
+            read => filled += read,
+        }
+    }
+    file.seek(SeekFrom::Start(0))?;
+
+    Ok(if header[..filled].starts_with(&[0x1F, 0x8B]) {
+        Some(ArchiveFormat::TarGz)
+    } else if header[..filled].starts_with(b"PK\x03\x04") || header[..filled].starts_with(b"PK\x05\x06") {
+        Some(ArchiveFormat::Zip)
+    } else if (filled >= 262 && &header[257..262] == b"ustar")
+        || (filled == header.len() && has_valid_checksum(&header))
+    {
+        Some(ArchiveFormat::Tar)
+    } else {
+        None
+    })
+}
+
+// The checksum field (bytes 148..156) holds, in octal, the sum of all header
+// bytes with the field itself counted as spaces. Some old tools summed signed
+// bytes, so either sum is accepted.
+fn has_valid_checksum(header: &[u8; 512]) -> bool {
+    let field = &header[148..156];
+    let digits: String = field
+        .iter()
+        .skip_while(|&&b| b == b' ')
+        .take_while(|&&b| (b'0'..=b'7').contains(&b))
+        .map(|&b| b as char)
+        .collect();
+    let expected = match u32::from_str_radix(&digits, 8) {
+        Ok(expected) => expected,
+        Err(_) => return false,
+    };
+
+    let field_range = 148..156;
+    let (unsigned, signed) = header.iter().enumerate().fold((0u32, 0i32), |(unsigned, signed), (i, &b)| {
+        let b = if field_range.contains(&i) { b' ' } else { b };
+        (unsigned + u32::from(b), signed + i32::from(b as i8))
+    });
+
+    // An all-zero block would otherwise match with a zero checksum
+    unsigned != 8 * u32::from(b' ') && (expected == unsigned || i64::from(expected) == i64::from(signed))
+}
+
+fn is_safe_entry_path(path: &Path) -> bool {
+    path.components().all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn v7_header(name: &str) -> Vec<u8> {
+        let mut header = vec![0u8; 512];
+        header[..name.len()].copy_from_slice(name.as_bytes());
+        header[100..108].copy_from_slice(b"0000644\0");
+        header[124..136].copy_from_slice(b"00000000000\0");
+        header[148..156].copy_from_slice(b"        ");
+        let sum: u32 = header.iter().map(|&b| u32::from(b)).sum();
+        header[148..156].copy_from_slice(format!("{:06o}\0 ", sum).as_bytes());
+        header
+    }
+
+    #[test]
+    fn v7_tar_is_detected_by_its_checksum() {
+        let mut archive = v7_header("notes.txt");
+        archive.resize(2048, 0);
+        assert_eq!(detect_format(&mut Cursor::new(&archive)).unwrap(), Some(ArchiveFormat::Tar));
+
+        // A corrupted checksum means it's not a tar header
+        archive[0] = b'm';
+        assert_eq!(detect_format(&mut Cursor::new(&archive)).unwrap(), None);
+    }
+
+    #[test]
+    fn zeros_and_zip_are_not_tar() {
+        assert_eq!(detect_format(&mut Cursor::new(vec![0u8; 1024])).unwrap(), None);
+        let zip = b"PK\x03\x04\x14\x00".to_vec();
+        assert_eq!(detect_format(&mut Cursor::new(zip)).unwrap(), Some(ArchiveFormat::Zip));
+    }
+}
@@ -0,0 +1,240 @@
+use serde::Serialize;
+
+#[derive(Debug, Default, Serialize)]
+pub struct Association {
+    // Name of the application the OS would open this file with
+    pub app_name: Option<String>,
+    // Icon for the file's type as a data URL
+    pub icon: Option<String>,
+}
+
+// Anything the platform can't tell us comes back as None rather than an error
+#[tauri::command]
+pub async fn get_file_association(path: String) -> Result<Association, String> {
+    if !std::path::Path::new(&path).exists() {
+        return Err(format!("{} does not exist", path));
+    }
+
+    Ok(lookup(&path))
+}
+
+#[cfg(windows)]
+fn lookup(path: &str) -> Association {
+    Association {
+        app_name: windows::friendly_app_name(path),
+        icon: windows::icon_data_url(path),
+    }
+}
+
+#[cfg(windows)]
+mod windows {
+    use std::path::Path;
+    use windows_sys::Win32::Graphics::Gdi::{
+        DeleteObject, GetDC, GetDIBits, GetObjectW, ReleaseDC, BITMAP, BITMAPINFO, BITMAPINFOHEADER, BI_RGB,
+        DIB_RGB_COLORS,
+    };
+    use windows_sys::Win32::UI::Shell::{
+        AssocQueryStringW, SHGetFileInfoW, ASSOCF_NONE, ASSOCSTR_FRIENDLYAPPNAME, SHFILEINFOW, SHGFI_ICON,
+        SHGFI_LARGEICON,
+    };
+    use windows_sys::Win32::UI::WindowsAndMessaging::{DestroyIcon, GetIconInfo, ICONINFO};
+
+    fn wide(value: &str) -> Vec<u16> {
+        value.encode_utf16().chain(Some(0)).collect()
+    }
+
+    pub fn friendly_app_name(path: &str) -> Option<String> {
+        let extension = Path::new(path).extension()?.to_string_lossy();
+        let extension = wide(&format!(".{}", extension));
+        let mut buffer = [0u16; 512];
+        let mut len = buffer.len() as u32;
+
+        let result = unsafe {
+            AssocQueryStringW(
+                ASSOCF_NONE,
+                ASSOCSTR_FRIENDLYAPPNAME,
+                extension.as_ptr(),
+                std::ptr::null(),
+                buffer.as_mut_ptr(),
+                &mut len,
+            )
+        };
+        if result != 0 || len == 0 {
+            return None;
+        }
+
+        // len includes the terminating NUL
+        Some(String::from_utf16_lossy(&buffer[..len as usize - 1]))
+    }
+
+    // Pulls the shell's large icon for the file and encodes its color bitmap
+    // as a BMP, which the webview can render directly
+    pub fn icon_data_url(path: &str) -> Option<String> {
+        let path = wide(path);
+        let mut info: SHFILEINFOW = unsafe { std::mem::zeroed() };
+
+        unsafe {
+            let found = SHGetFileInfoW(
+                path.as_ptr(),
+                0,
+                &mut info,
+                std::mem::size_of::<SHFILEINFOW>() as u32,
+                SHGFI_ICON | SHGFI_LARGEICON,
+            );
+            if found == 0 || info.hIcon.is_null() {
+                return None;
+            }
+
+            let mut icon_info: ICONINFO = std::mem::zeroed();
+            let pixels = if GetIconInfo(info.hIcon, &mut icon_info) != 0 {
+                let bmp = bitmap_bytes(icon_info.hbmColor);
+                DeleteObject(icon_info.hbmColor);
+                DeleteObject(icon_info.hbmMask);
+                bmp
+            } else {
+                None
+            };
+            DestroyIcon(info.hIcon);
+
+            pixels.map(|bytes| crate::encode_data_url("image/bmp", &bytes))
+        }
+    }
+
+    unsafe fn bitmap_bytes(handle: windows_sys::Win32::Graphics::Gdi::HBITMAP) -> Option<Vec<u8>> {
+        if handle.is_null() {
+            return None;
+        }
+
+        let mut bitmap: BITMAP = std::mem::zeroed();
+        if GetObjectW(handle, std::mem::size_of::<BITMAP>() as i32, &mut bitmap as *mut _ as *mut _) == 0 {
+            return None;
+        }
+
+        let header = BITMAPINFOHEADER {
+            biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+            biWidth: bitmap.bmWidth,
+            biHeight: bitmap.bmHeight,
+            biPlanes: 1,
+            biBitCount: 32,
+            biCompression: BI_RGB,
+            biSizeImage: 0,
+            biXPelsPerMeter: 0,
+            biYPelsPerMeter: 0,
+            biClrUsed: 0,
+            biClrImportant: 0,
+        };
+        let mut info = BITMAPINFO {
+            bmiHeader: header,
+            bmiColors: [std::mem::zeroed()],
+        };
+
+        let image_size = (bitmap.bmWidth * bitmap.bmHeight * 4) as usize;
+        let mut pixels = vec![0u8; image_size];
+        let dc = GetDC(std::ptr::null_mut());
+        let lines = GetDIBits(
+            dc,
+            handle,
+            0,
+            bitmap.bmHeight as u32,
+            pixels.as_mut_ptr() as *mut _,
+            &mut info,
+            DIB_RGB_COLORS,
+        );
+        ReleaseDC(std::ptr::null_mut(), dc);
+        if lines == 0 {
+            return None;
+        }
+
+        // BITMAPFILEHEADER followed by the same bottom-up BITMAPINFOHEADER
+        // GetDIBits filled the pixels for
+        let offset = 14 + header.biSize;
+        let mut bytes = Vec::with_capacity(offset as usize + image_size);
+        bytes.extend_from_slice(b"BM");
+        bytes.extend_from_slice(&(offset + image_size as u32).to_le_bytes());
+        bytes.extend_from_slice(&[0; 4]);
+        bytes.extend_from_slice(&offset.to_le_bytes());
+        bytes.extend_from_slice(&header.biSize.to_le_bytes());
+        bytes.extend_from_slice(&header.biWidth.to_le_bytes());
+        bytes.extend_from_slice(&header.biHeight.to_le_bytes());
+        bytes.extend_from_slice(&header.biPlanes.to_le_bytes());
+        bytes.extend_from_slice(&header.biBitCount.to_le_bytes());
+        bytes.extend_from_slice(&[0; 24]);
+        bytes.extend_from_slice(&pixels);
+        Some(bytes)
+    }
+}
+
+// JXA gives us NSWorkspace without pulling in Objective-C bindings. The
+// script prints the app name on the first line and a base64 PNG on the second.
+#[cfg(target_os = "macos")]
+const MACOS_ASSOCIATION_SCRIPT: &str = r#"
+ObjC.import('AppKit');
+function run(argv) {
+    const workspace = $.NSWorkspace.sharedWorkspace;
+    const app = workspace.URLForApplicationToOpenURL($.NSURL.fileURLWithPath(argv[0]));
+    const name = app.isNil() ? '' : ObjC.unwrap(app.URLByDeletingPathExtension.lastPathComponent);
+    const image = workspace.iconForFile(argv[0]);
+    image.setSize($.NSMakeSize(32, 32));
+    const rep = $.NSBitmapImageRep.alloc.initWithCGImage(image.CGImageForProposedRectContextHints(null, null, null));
+    const png = rep.representationUsingTypeProperties(4, $());
+    return name + '\n' + ObjC.unwrap(png.base64EncodedStringWithOptions(0));
+}
+"#;
+
+#[cfg(target_os = "macos")]
+fn lookup(path: &str) -> Association {
+    let output = std::process::Command::new("osascript")
+        .args(["-l", "JavaScript", "-e", MACOS_ASSOCIATION_SCRIPT, path])
+        .output();
+
+    let stdout = match output {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout).to_string(),
+        _ => return Association::default(),
+    };
+
+    let mut lines = stdout.lines();
+    let non_empty = |line: Option<&str>| line.map(str::trim).filter(|l| !l.is_empty()).map(str::to_string);
+
+    Association {
+        app_name: non_empty(lines.next()),
+        icon: non_empty(lines.next()).map(|png| format!("data:image/png;base64,{}", png)),
+    }
+}
+
+// Linux has no per-type icon we can render without an icon theme lookup, so
+// only the default application's name is resolved, via xdg-mime
+#[cfg(all(unix, not(target_os = "macos")))]
+fn lookup(path: &str) -> Association {
+    let query = |args: &[&str]| {
+        std::process::Command::new("xdg-mime")
+            .args(args)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+
+    let app_name = query(&["query", "filetype", path])
+        .and_then(|mime| query(&["query", "default", &mime]))
+        .map(|desktop| desktop_entry_name(&desktop).unwrap_or_else(|| desktop.trim_end_matches(".desktop").to_string()));
+
+    Association { app_name, icon: None }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn desktop_entry_name(desktop_file: &str) -> Option<String> {
+    let home = std::env::var("HOME").unwrap_or_default();
+    let data_home = std::env::var("XDG_DATA_HOME").unwrap_or_else(|_| format!("{}/.local/share", home));
+    let data_dirs = std::env::var("XDG_DATA_DIRS").unwrap_or_else(|_| "/usr/local/share:/usr/share".to_string());
+
+    std::iter::once(data_home.as_str())
+        .chain(data_dirs.split(':'))
+        .map(|dir| std::path::Path::new(dir).join("applications").join(desktop_file))
+        .find_map(|path| std::fs::read_to_string(path).ok())
+        .and_then(|contents| {
+            contents
+                .lines()
+                .find_map(|line| line.strip_prefix("Name=").map(str::to_string))
+        })
+}
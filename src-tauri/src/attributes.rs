@@ -0,0 +1,255 @@
+use serde::Serialize;
+use std::path::Path;
+
+use crate::walk;
+
+#[derive(Debug, Serialize)]
+pub struct LinkInfo {
+    // Directory entries pointing at this file, None where it can't be read
+    pub hard_links: Option<u64>,
+    pub is_symlink: bool,
+    // Whether the file shares storage with another (a reflink/clone, or a
+    // snapshot on btrfs), None where that can't be told
+    pub shares_extents: Option<bool>,
+}
+
+#[tauri::command]
+pub async fn is_hidden(path: String) -> Result<bool, String> {
+    let path = Path::new(&path);
+    if !path.exists() {
+        return Err(format!("{} does not exist", path.display()));
+    }
+
+    Ok(walk::is_hidden(path))
+}
+
+#[cfg(windows)]
+#[tauri::command]
+pub async fn set_hidden(path: String, hidden: bool) -> Result<(), String> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Storage::FileSystem::{
+        GetFileAttributesW, SetFileAttributesW, FILE_ATTRIBUTE_HIDDEN, INVALID_FILE_ATTRIBUTES,
+    };
+
+    let wide: Vec<u16> = Path::new(&path).as_os_str().encode_wide().chain(Some(0)).collect();
+
+    unsafe {
+        let attributes = GetFileAttributesW(wide.as_ptr());
+        if attributes == INVALID_FILE_ATTRIBUTES {
+            return Err(std::io::Error::last_os_error().to_string());
+        }
+
+        let attributes = if hidden {
+            attributes | FILE_ATTRIBUTE_HIDDEN
+        } else {
+            attributes & !FILE_ATTRIBUTE_HIDDEN
+        };
+
+        if SetFileAttributesW(wide.as_ptr(), attributes) == 0 {
+            return Err(std::io::Error::last_os_error().to_string());
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(not(windows))]
+#[tauri::command]
+pub async fn set_hidden(path: String, hidden: bool) -> Result<(), String> {
+    let _ = (path, hidden);
+    Err("Unsupported: hiding is controlled by a leading dot in the file name on this platform".to_string())
+}
+
+// Whether the path could be saved to, without touching it. For a path that
+// doesn't exist yet this checks the nearest existing directory it would be
+// created in.
+#[tauri::command]
+pub async fn can_write(path: String) -> Result<bool, String> {
+    let path = Path::new(&path);
+    if path.exists() {
+        return Ok(writable(path));
+    }
+
+    let parent = path
+        .ancestors()
+        .skip(1)
+        .find(|ancestor| ancestor.is_dir())
+        .ok_or_else(|| format!("No existing parent directory for {}", path.display()))?;
+    Ok(writable(parent))
+}
+
+#[cfg(unix)]
+fn writable(path: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    let metadata = match std::fs::metadata(path) {
+        Ok(metadata) => metadata,
+        Err(_) => return false,
+    };
+
+    let (euid, egid) = unsafe { (libc::geteuid(), libc::getegid()) };
+    if euid == 0 {
+        return true;
+    }
+
+    let mode = metadata.mode();
+    let in_group = metadata.gid() == egid || supplementary_groups().contains(&metadata.gid());
+    let (write_bit, exec_bit) = if metadata.uid() == euid {
+        (0o200, 0o100)
+    } else if in_group {
+        (0o020, 0o010)
+    } else {
+        (0o002, 0o001)
+    };
+
+    // Creating files in a directory also needs search permission on it
+    mode & write_bit != 0 && (!metadata.is_dir() || mode & exec_bit != 0)
+}
+
+#[cfg(unix)]
+fn supplementary_groups() -> Vec<libc::gid_t> {
+    unsafe {
+        let count = libc::getgroups(0, std::ptr::null_mut());
+        if count <= 0 {
+            return Vec::new();
+        }
+
+        let mut groups = vec![0; count as usize];
+        let count = libc::getgroups(count, groups.as_mut_ptr());
+        groups.truncate(count.max(0) as usize);
+        groups
+    }
+}
+
+// Opening for write access (without truncating) is the only reliable check on
+// Windows, since ACLs aren't reflected in the read-only attribute. Directories
+// need backup semantics to be opened at all, and write access on a directory
+// handle means permission to add files to it.
+#[cfg(windows)]
+fn writable(path: &Path) -> bool {
+    use std::os::windows::fs::OpenOptionsExt;
+    use windows_sys::Win32::Storage::FileSystem::FILE_FLAG_BACKUP_SEMANTICS;
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true);
+    if path.is_dir() {
+        options.custom_flags(FILE_FLAG_BACKUP_SEMANTICS);
+    }
+
+    options.open(path).is_ok()
+}
+
+// Explains why deleting one "copy" may not free any space
+#[tauri::command]
+pub async fn get_link_info(path: String) -> Result<LinkInfo, String> {
+    let path = Path::new(&path);
+    let metadata = std::fs::symlink_metadata(path).map_err(|e| e.to_string())?;
+    let is_symlink = metadata.file_type().is_symlink();
+
+    Ok(LinkInfo {
+        hard_links: hard_link_count(path, &metadata),
+        is_symlink,
+        shares_extents: if metadata.is_file() { shares_extents(path) } else { None },
+    })
+}
+
+#[cfg(unix)]
+fn hard_link_count(_path: &Path, metadata: &std::fs::Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+
+    Some(metadata.nlink())
+}
+
+#[cfg(windows)]
+fn hard_link_count(path: &Path, metadata: &std::fs::Metadata) -> Option<u64> {
+    use std::os::windows::fs::OpenOptionsExt;
+    use std::os::windows::io::AsRawHandle;
+    use windows_sys::Win32::Storage::FileSystem::{
+        GetFileInformationByHandle, BY_HANDLE_FILE_INFORMATION, FILE_FLAG_BACKUP_SEMANTICS,
+        FILE_FLAG_OPEN_REPARSE_POINT,
+    };
+
+    // No access rights are needed just to read the file's information
+    let mut options = std::fs::OpenOptions::new();
+    options.access_mode(0);
+    let mut flags = FILE_FLAG_OPEN_REPARSE_POINT;
+    if metadata.is_dir() {
+        flags |= FILE_FLAG_BACKUP_SEMANTICS;
+    }
+    options.custom_flags(flags);
+    let file = options.open(path).ok()?;
+
+    let mut info: BY_HANDLE_FILE_INFORMATION = unsafe { std::mem::zeroed() };
+    if unsafe { GetFileInformationByHandle(file.as_raw_handle() as _, &mut info) } == 0 {
+        return None;
+    }
+    Some(u64::from(info.nNumberOfLinks))
+}
+
+#[cfg(not(any(unix, windows)))]
+fn hard_link_count(_path: &Path, _metadata: &std::fs::Metadata) -> Option<u64> {
+    None
+}
+
+// Asks for the file's extent map and looks for any extent flagged as shared
+#[cfg(target_os = "linux")]
+fn shares_extents(path: &Path) -> Option<bool> {
+    use std::os::unix::io::AsRawFd;
+
+    // struct fiemap and struct fiemap_extent from linux/fiemap.h
+    #[repr(C)]
+    struct FiemapExtent {
+        logical: u64,
+        physical: u64,
+        length: u64,
+        reserved64: [u64; 2],
+        flags: u32,
+        reserved: [u32; 3],
+    }
+
+    #[repr(C)]
+    struct Fiemap {
+        start: u64,
+        length: u64,
+        flags: u32,
+        mapped_extents: u32,
+        extent_count: u32,
+        reserved: u32,
+        extents: [FiemapExtent; EXTENTS_PER_CALL],
+    }
+
+    const EXTENTS_PER_CALL: usize = 32;
+    // _IOWR('f', 11, struct fiemap)
+    const FS_IOC_FIEMAP: libc::c_ulong = 0xC020_660B;
+    const FIEMAP_EXTENT_LAST: u32 = 0x1;
+    const FIEMAP_EXTENT_SHARED: u32 = 0x2000;
+
+    let file = std::fs::File::open(path).ok()?;
+    let mut start = 0u64;
+
+    loop {
+        let mut map: Fiemap = unsafe { std::mem::zeroed() };
+        map.start = start;
+        map.length = u64::MAX - start;
+        map.extent_count = EXTENTS_PER_CALL as u32;
+
+        // Filesystems without extent maps (tmpfs, many network mounts) refuse the call
+        if unsafe { libc::ioctl(file.as_raw_fd(), FS_IOC_FIEMAP as _, &mut map) } != 0 {
+            return None;
+        }
+
+        let extents = &map.extents[..(map.mapped_extents as usize).min(EXTENTS_PER_CALL)];
+        if extents.iter().any(|extent| extent.flags & FIEMAP_EXTENT_SHARED != 0) {
+            return Some(true);
+        }
+        match extents.last() {
+            Some(last) if last.flags & FIEMAP_EXTENT_LAST == 0 => start = last.logical + last.length,
+            _ => return Some(false),
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn shares_extents(_path: &Path) -> Option<bool> {
+    None
+}
@@ -0,0 +1,134 @@
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+
+use crate::{language, text, walk};
+
+// Enough for every signature checked below
+const MAGIC_SNIFF_BYTES: u64 = 512;
+
+// Icon categories for the tree
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum FileKind {
+    Directory,
+    Symlink,
+    Code,
+    Text,
+    Image,
+    Audio,
+    Video,
+    Archive,
+    Document,
+    Font,
+    Executable,
+    Binary,
+}
+
+const EXTENSION_KINDS: &[(FileKind, &[&str])] = &[
+    (FileKind::Text, &["txt", "md", "markdown", "rst", "log", "csv", "tsv", "ini", "cfg", "conf", "env"]),
+    (FileKind::Image, &["png", "jpg", "jpeg", "gif", "webp", "bmp", "svg", "ico", "tif", "tiff", "avif", "heic"]),
+    (FileKind::Audio, &["mp3", "wav", "flac", "ogg", "m4a", "aac", "opus"]),
+    (FileKind::Video, &["mp4", "mkv", "mov", "avi", "webm", "m4v", "wmv"]),
+    (FileKind::Archive, &["zip", "tar", "gz", "tgz", "bz2", "xz", "7z", "rar", "zst"]),
+    (FileKind::Document, &["pdf", "doc", "docx", "xls", "xlsx", "ppt", "pptx", "odt", "ods", "odp", "rtf", "epub"]),
+    (FileKind::Font, &["ttf", "otf", "woff", "woff2"]),
+    (FileKind::Executable, &["exe", "dll", "so", "dylib", "app", "msi", "bin"]),
+];
+
+#[tauri::command]
+pub async fn classify_file(path: String) -> Result<FileKind, String> {
+    let path = Path::new(&path);
+    let metadata = fs::symlink_metadata(path).map_err(|e| e.to_string())?;
+    Ok(classify(path, &metadata))
+}
+
+// Kinds for every immediate child of path in one call, keyed by child path
+#[tauri::command]
+pub async fn classify_directory(path: String) -> Result<Vec<(String, FileKind)>, String> {
+    let mut entries: Vec<_> = fs::read_dir(&path)
+        .map_err(|e| e.to_string())?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| !walk::is_ignored(&entry.path()))
+        .collect();
+    entries.sort_by_key(|entry| entry.file_name());
+
+    Ok(entries
+        .into_iter()
+        .filter_map(|entry| {
+            let path = entry.path();
+            let metadata = entry.metadata().ok()?;
+            let kind = classify(&path, &metadata);
+            Some((path.to_string_lossy().to_string(), kind))
+        })
+        .collect())
+}
+
+// The extension decides when it's recognised; only files without a known one
+// are opened and sniffed
+fn classify(path: &Path, metadata: &fs::Metadata) -> FileKind {
+    if metadata.file_type().is_symlink() {
+        return FileKind::Symlink;
+    }
+    if metadata.is_dir() {
+        return FileKind::Directory;
+    }
+
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    if let Some((kind, _)) = EXTENSION_KINDS.iter().find(|(_, extensions)| extensions.contains(&extension.as_str())) {
+        return *kind;
+    }
+    if !extension.is_empty() && language::detect_language(path).is_some() {
+        return FileKind::Code;
+    }
+
+    sniff(path)
+}
+
+fn sniff(path: &Path) -> FileKind {
+    let mut head = Vec::new();
+    let read = File::open(path).and_then(|file| file.take(MAGIC_SNIFF_BYTES).read_to_end(&mut head));
+    if read.is_err() {
+        return FileKind::Binary;
+    }
+
+    if crate::sniff_image_type(&head).is_some() {
+        FileKind::Image
+    } else if head.starts_with(b"%PDF-") {
+        FileKind::Document
+    } else if head.starts_with(b"PK\x03\x04") || head.starts_with(&[0x1F, 0x8B]) || head.get(257..262) == Some(b"ustar") {
+        FileKind::Archive
+    } else if is_executable_header(&head) {
+        FileKind::Executable
+    } else if head.starts_with(b"#!") {
+        // Scripts without an extension, named by their interpreter
+        if language::detect_language(path).is_some() {
+            FileKind::Code
+        } else {
+            FileKind::Executable
+        }
+    } else if text::detect_bom(&head).is_some() || !text::is_binary(&head) {
+        FileKind::Text
+    } else {
+        FileKind::Binary
+    }
+}
+
+// ELF, PE and Mach-O (32/64-bit in either byte order, plus universal)
+fn is_executable_header(head: &[u8]) -> bool {
+    head.starts_with(b"\x7fELF")
+        || head.starts_with(b"MZ")
+        || [
+            [0xFE, 0xED, 0xFA, 0xCE],
+            [0xFE, 0xED, 0xFA, 0xCF],
+            [0xCE, 0xFA, 0xED, 0xFE],
+            [0xCF, 0xFA, 0xED, 0xFE],
+            [0xCA, 0xFE, 0xBA, 0xBE],
+        ]
+        .iter()
+        .any(|magic| head.starts_with(magic))
+}
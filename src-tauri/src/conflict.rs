@@ -0,0 +1,176 @@
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+use crate::{file_ops, text};
+
+#[derive(Debug, Serialize)]
+pub struct ConflictRegion {
+    // 1-based line numbers of the <<<<<<<, ======= and >>>>>>> markers
+    pub start_line: usize,
+    pub separator_line: usize,
+    pub end_line: usize,
+    // The ||||||| marker opening the common ancestor in diff3-style conflicts
+    pub base_line: Option<usize>,
+    // Whatever follows the opening and closing markers, usually a branch name
+    pub ours_label: String,
+    pub theirs_label: String,
+    pub ours: String,
+    pub base: Option<String>,
+    pub theirs: String,
+}
+
+#[tauri::command]
+pub async fn find_conflict_markers(path: String) -> Result<Vec<ConflictRegion>, String> {
+    let content = read_text(&path)?;
+    Ok(find_regions(&split_lines(&content)))
+}
+
+// Rewrites the file with one conflict replaced by the chosen side: "ours",
+// "theirs", or "both" (ours followed by theirs). Other conflicts are left as
+// they are, so the indexes of those after it shift down by one.
+#[tauri::command]
+pub async fn resolve_conflict(path: String, region_index: usize, choice: String) -> Result<(), String> {
+    let content = read_text(&path)?;
+    let lines = split_lines(&content);
+    let regions = find_regions(&lines);
+    let region = regions
+        .get(region_index)
+        .ok_or_else(|| format!("There is no conflict {} in {}", region_index, path))?;
+
+    let ours_end = region.base_line.unwrap_or(region.separator_line) - 1;
+    let ours = &lines[region.start_line..ours_end];
+    let theirs = &lines[region.separator_line..region.end_line - 1];
+    let kept: Vec<&str> = match choice.as_str() {
+        "ours" => ours.to_vec(),
+        "theirs" => theirs.to_vec(),
+        "both" => ours.iter().chain(theirs).copied().collect(),
+        _ => return Err(format!("Unsupported: unknown choice {}", choice)),
+    };
+
+    let mut resolved = String::with_capacity(content.len());
+    resolved.extend(lines[..region.start_line - 1].iter().copied());
+    resolved.extend(kept);
+    resolved.extend(lines[region.end_line..].iter().copied());
+    file_ops::write_atomic(Path::new(&path), resolved.as_bytes()).map_err(|e| e.to_string())
+}
+
+fn read_text(path: &str) -> Result<String, String> {
+    let bytes = fs::read(path).map_err(|e| e.to_string())?;
+    if text::is_binary(&bytes) {
+        return Err(format!("{} is a binary file", path));
+    }
+    String::from_utf8(bytes).map_err(|_| format!("{} is not valid UTF-8", path))
+}
+
+// Lines with their endings, so a rewrite keeps the file's own
+fn split_lines(content: &str) -> Vec<&str> {
+    content.split_inclusive('\n').collect()
+}
+
+// The text after a marker, or None if the line isn't that marker. Markers are
+// exactly seven characters, alone or followed by a space and a label.
+fn marker<'a>(line: &'a str, marker: &str) -> Option<&'a str> {
+    let rest = line.trim_end_matches(['\r', '\n']).strip_prefix(marker)?;
+    if rest.is_empty() || rest.starts_with(' ') {
+        Some(rest.trim())
+    } else {
+        None
+    }
+}
+
+// Complete regions only; an opening marker without a matching separator and
+// closing marker is ordinary text
+fn find_regions(lines: &[&str]) -> Vec<ConflictRegion> {
+    let mut regions = Vec::new();
+    let mut index = 0;
+
+    while index < lines.len() {
+        match marker(lines[index], "<<<<<<<").and_then(|label| region_at(lines, index, label)) {
+            Some(region) => {
+                index = region.end_line;
+                regions.push(region);
+            }
+            None => index += 1,
+        }
+    }
+    regions
+}
+
+fn region_at(lines: &[&str], start: usize, ours_label: &str) -> Option<ConflictRegion> {
+    let mut base = None;
+    let mut separator = None;
+
+    for (index, line) in lines.iter().enumerate().skip(start + 1) {
+        // A second opening marker means the first was never closed
+        if marker(line, "<<<<<<<").is_some() {
+            return None;
+        }
+        match separator {
+            None if base.is_none() && marker(line, "|||||||").is_some() => base = Some(index),
+            None if marker(line, "=======").is_some() => separator = Some(index),
+            Some(separator) => {
+                if let Some(theirs_label) = marker(line, ">>>>>>>") {
+                    let ours_end = base.unwrap_or(separator);
+                    return Some(ConflictRegion {
+                        start_line: start + 1,
+                        separator_line: separator + 1,
+                        end_line: index + 1,
+                        base_line: base.map(|base| base + 1),
+                        ours_label: ours_label.to_string(),
+                        theirs_label: theirs_label.to_string(),
+                        ours: lines[start + 1..ours_end].concat(),
+                        base: base.map(|base| lines[base + 1..separator].concat()),
+                        theirs: lines[separator + 1..index].concat(),
+                    });
+                }
+            }
+            None => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_two_way_and_diff3_regions() {
+        let content = "\
+keep
+<<<<<<< HEAD
+ours
+=======
+theirs
+>>>>>>> feature
+middle
+<<<<<<< ours
+a
+||||||| base
+b
+=======
+c
+>>>>>>> theirs
+";
+        let regions = find_regions(&split_lines(content));
+        assert_eq!(regions.len(), 2);
+
+        let first = &regions[0];
+        assert_eq!((first.start_line, first.separator_line, first.end_line), (2, 4, 6));
+        assert_eq!((first.ours_label.as_str(), first.theirs_label.as_str()), ("HEAD", "feature"));
+        assert_eq!((first.ours.as_str(), first.theirs.as_str()), ("ours\n", "theirs\n"));
+        assert_eq!(first.base, None);
+
+        let second = &regions[1];
+        assert_eq!(second.base_line, Some(10));
+        assert_eq!(second.base.as_deref(), Some("b\n"));
+        assert_eq!((second.ours.as_str(), second.theirs.as_str()), ("a\n", "c\n"));
+    }
+
+    #[test]
+    fn ignores_unclosed_regions_and_lookalike_lines() {
+        let content = "<<<<<<< HEAD\nours\n=======\n<<<<<<<< not a marker\n=======x\n";
+        assert!(find_regions(&split_lines(content)).is_empty());
+    }
+}
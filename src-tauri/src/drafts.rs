@@ -0,0 +1,105 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::Manager;
+
+use crate::{file_ops, hashing};
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredDraft {
+    path: String,
+    content: String,
+    saved_at: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Draft {
+    pub path: String,
+    pub content: String,
+    pub saved_at: u64,
+    // Whether the draft was saved after the file last changed on disk, which
+    // is when recovery is worth offering
+    pub newer_than_file: bool,
+}
+
+// Saves the editor buffer for path as a draft under app data. The file
+// itself is never touched.
+#[tauri::command]
+pub async fn save_draft(app: tauri::AppHandle, path: String, content: String) -> Result<(), String> {
+    let draft_path = draft_path(&app, &path)?;
+    file_ops::ensure_parent(&draft_path)?;
+
+    let saved_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let json = serde_json::to_vec(&StoredDraft { path, content, saved_at }).map_err(|e| e.to_string())?;
+    file_ops::write_atomic(&draft_path, &json).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn load_draft(app: tauri::AppHandle, path: String) -> Result<Option<Draft>, String> {
+    read_draft(&draft_path(&app, &path)?)
+}
+
+#[tauri::command]
+pub async fn discard_draft(app: tauri::AppHandle, path: String) -> Result<bool, String> {
+    match fs::remove_file(draft_path(&app, &path)?) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+// Every saved draft, for offering recovery on startup
+#[tauri::command]
+pub async fn list_drafts(app: tauri::AppHandle) -> Result<Vec<Draft>, String> {
+    let entries = match fs::read_dir(drafts_dir(&app)?) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.to_string()),
+    };
+
+    let mut drafts = Vec::new();
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        // Unreadable or half-written drafts are skipped rather than failing the list
+        if let Ok(Some(draft)) = read_draft(&entry.path()) {
+            drafts.push(draft);
+        }
+    }
+    drafts.sort_by_key(|draft| std::cmp::Reverse(draft.saved_at));
+    Ok(drafts)
+}
+
+fn read_draft(draft_path: &Path) -> Result<Option<Draft>, String> {
+    let bytes = match fs::read(draft_path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.to_string()),
+    };
+    let stored: StoredDraft = serde_json::from_slice(&bytes).map_err(|e| format!("Invalid draft: {}", e))?;
+
+    // A file that no longer exists leaves the draft as the only copy
+    let file_modified = fs::metadata(&stored.path).ok().and_then(|m| crate::modified_secs(&m));
+    Ok(Some(Draft {
+        newer_than_file: file_modified.map_or(true, |modified| stored.saved_at >= modified),
+        path: stored.path,
+        content: stored.content,
+        saved_at: stored.saved_at,
+    }))
+}
+
+fn drafts_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join("drafts"))
+        .map_err(|e| e.to_string())
+}
+
+// Drafts are named by a hash of the file's path, which is always a safe file name
+fn draft_path(app: &tauri::AppHandle, path: &str) -> Result<PathBuf, String> {
+    let name = hashing::to_hex(&Sha256::digest(path.as_bytes()));
+    Ok(drafts_dir(app)?.join(format!("{}.json", name)))
+}
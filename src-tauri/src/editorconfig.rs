@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+// Resolves the .editorconfig properties that apply to path: files are read
+// from the outermost (stopping at one marked root = true) inwards, so nearer
+// files and later sections win. Keys and values are lowercased.
+pub fn properties(path: &Path) -> HashMap<String, String> {
+    let mut configs = Vec::new();
+    for dir in path.ancestors().skip(1) {
+        if let Ok(contents) = fs::read_to_string(dir.join(".editorconfig")) {
+            let is_root = parse(&contents)
+                .first()
+                .map(|(section, props)| section.is_none() && props.get("root").map(String::as_str) == Some("true"))
+                .unwrap_or(false);
+            configs.push((dir.to_path_buf(), contents));
+            if is_root {
+                break;
+            }
+        }
+    }
+
+    let mut properties = HashMap::new();
+    for (dir, contents) in configs.iter().rev() {
+        let relative = crate::walk::relative_path(dir, path);
+        for (section, props) in parse(contents) {
+            if section.is_some_and(|glob| section_matches(&glob, &relative)) {
+                properties.extend(props);
+            }
+        }
+    }
+    properties
+}
+
+// Sections in file order; the preamble before the first header has no glob
+fn parse(contents: &str) -> Vec<(Option<String>, HashMap<String, String>)> {
+    let mut sections = vec![(None, HashMap::new())];
+
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+
+        if let Some(glob) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            sections.push((Some(glob.to_string()), HashMap::new()));
+        } else if let Some((key, value)) = line.split_once('=') {
+            if let Some((_, props)) = sections.last_mut() {
+                props.insert(key.trim().to_lowercase(), value.trim().to_lowercase());
+            }
+        }
+    }
+
+    sections
+}
+
+// Globs without a "/" match the file name at any depth; others are relative
+// to the directory holding the .editorconfig
+fn section_matches(glob: &str, relative: &str) -> bool {
+    let glob = if glob.contains('/') {
+        glob.trim_start_matches('/').to_string()
+    } else {
+        format!("**/{}", glob)
+    };
+    let options = glob::MatchOptions {
+        case_sensitive: true,
+        require_literal_separator: true,
+        require_literal_leading_dot: false,
+    };
+
+    expand_braces(&glob).iter().any(|pattern| {
+        glob::Pattern::new(pattern)
+            .map(|p| p.matches_with(relative, options))
+            .unwrap_or(false)
+    })
+}
+
+// The glob crate has no "{a,b}" alternation, so expand it into separate patterns
+fn expand_braces(glob: &str) -> Vec<String> {
+    let (open, close) = match (glob.find('{'), glob.find('}')) {
+        (Some(open), Some(close)) if open < close => (open, close),
+        _ => return vec![glob.to_string()],
+    };
+
+    let (prefix, rest) = (&glob[..open], &glob[close + 1..]);
+    glob[open + 1..close]
+        .split(',')
+        .flat_map(|option| expand_braces(&format!("{}{}{}", prefix, option, rest)))
+        .collect()
+}
@@ -0,0 +1,492 @@
+use serde::Serialize;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{native, paths, roots};
+
+static NEXT_TEMP_ID: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Serialize)]
+pub struct WriteResult {
+    pub path: String,
+    pub ok: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MoveResult {
+    pub path: String,
+    pub new_path: Option<String>,
+    pub ok: bool,
+    pub error: Option<String>,
+}
+
+#[tauri::command]
+pub async fn rename_path(app: tauri::AppHandle, from: String, to: String) -> Result<(), String> {
+    let from = Path::new(&from);
+    let to = Path::new(&to);
+
+    if fs::symlink_metadata(from).is_err() {
+        return Err(format!("{} does not exist", from.display()));
+    }
+
+    if is_case_only_rename(from, to) {
+        rename_via_temporary(from, to)?;
+    } else {
+        if fs::symlink_metadata(to).is_ok() {
+            return Err(format!("{} already exists", to.display()));
+        }
+        fs::rename(from, to).map_err(|e| native::describe_error(from, &e))?;
+    }
+
+    roots::relocate(&app, from, to);
+    Ok(())
+}
+
+// Moves each path into dest_dir. Selections are normalized first so a folder
+// and files inside it aren't moved twice. Each move is independent, so one
+// failure doesn't stop the rest; the results say which paths moved and where.
+#[tauri::command]
+pub async fn move_paths(app: tauri::AppHandle, paths: Vec<String>, dest_dir: String) -> Result<Vec<MoveResult>, String> {
+    let dest_dir = Path::new(&dest_dir);
+    if !dest_dir.is_dir() {
+        return Err(format!("{} is not a directory", dest_dir.display()));
+    }
+
+    Ok(paths::normalize(paths)
+        .into_iter()
+        .map(|path| {
+            let result = move_into(&app, Path::new(&path), dest_dir);
+
+            MoveResult {
+                ok: result.is_ok(),
+                new_path: result.as_ref().ok().cloned(),
+                error: result.err(),
+                path,
+            }
+        })
+        .collect())
+}
+
+fn move_into(app: &tauri::AppHandle, from: &Path, dest_dir: &Path) -> Result<String, String> {
+    let name = from
+        .file_name()
+        .ok_or_else(|| format!("Cannot move {}", from.display()))?;
+    let to = dest_dir.join(name);
+
+    if fs::symlink_metadata(&to).is_ok() {
+        return Err(format!("{} already exists", to.display()));
+    }
+    move_path(from, &to).map_err(|e| move_error(from, &e))?;
+
+    roots::relocate(app, from, &to);
+    Ok(to.to_string_lossy().to_string())
+}
+
+// Moves path into archive_root/<year>/<month>/, creating the folders as
+// needed, and returns the new path. A name already taken there gets a
+// numeric suffix, as in "notes (2).md". The date is the current UTC one.
+#[tauri::command]
+pub async fn archive_path(app: tauri::AppHandle, path: String, archive_root: String) -> Result<String, String> {
+    let from = Path::new(&path);
+    if !from.exists() {
+        return Err(format!("{} does not exist", from.display()));
+    }
+    let archive_root = Path::new(&archive_root);
+    if archive_root.starts_with(from) {
+        return Err(format!("Cannot archive {} into itself", from.display()));
+    }
+    let name = from
+        .file_name()
+        .ok_or_else(|| format!("Cannot archive {}", from.display()))?;
+
+    let (year, month) = current_year_month();
+    let dest_dir = archive_root.join(format!("{:04}", year)).join(format!("{:02}", month));
+    fs::create_dir_all(&dest_dir).map_err(|e| e.to_string())?;
+
+    let to = unique_path(&dest_dir, &name.to_string_lossy());
+    move_path(from, &to).map_err(|e| move_error(from, &e))?;
+
+    roots::relocate(&app, from, &to);
+    Ok(to.to_string_lossy().to_string())
+}
+
+// dir/name, or the first of "stem (2).ext", "stem (3).ext"... not yet taken
+fn unique_path(dir: &Path, name: &str) -> PathBuf {
+    let candidate = dir.join(name);
+    if fs::symlink_metadata(&candidate).is_err() {
+        return candidate;
+    }
+
+    // A leading dot is part of the stem, so ".env" becomes ".env (2)"
+    let (stem, extension) = match name.rfind('.').filter(|&dot| dot > 0) {
+        Some(dot) => name.split_at(dot),
+        None => (name, ""),
+    };
+    (2..)
+        .map(|n| dir.join(format!("{} ({}){}", stem, n, extension)))
+        .find(|candidate| fs::symlink_metadata(candidate).is_err())
+        .expect("an unused name")
+}
+
+fn current_year_month() -> (i64, u32) {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs() as i64);
+    year_month(secs)
+}
+
+fn year_month(secs: i64) -> (i64, u32) {
+    // Days since 1970-01-01 to a civil date, counting years from March so
+    // the leap day falls at the end (Howard Hinnant's days_from_civil inverse)
+    let days = secs.div_euclid(86_400) + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month as u32)
+}
+
+// Renames where possible, falling back to copy-and-delete when the
+// destination is on another filesystem
+pub fn move_path(from: &Path, to: &Path) -> Result<(), io::Error> {
+    match fs::rename(from, to) {
+        Err(e) if is_cross_device(&e) => {
+            copy_recursive(from, to)?;
+            remove_any(from)
+        }
+        result => result,
+    }
+}
+
+// Sharing violations name the processes holding the file, as in rename_path
+fn move_error(from: &Path, error: &io::Error) -> String {
+    format!("Failed to move {}: {}", from.display(), native::describe_error(from, error))
+}
+
+fn is_cross_device(error: &io::Error) -> bool {
+    #[cfg(unix)]
+    let code = libc::EXDEV;
+    // ERROR_NOT_SAME_DEVICE
+    #[cfg(windows)]
+    let code = 17;
+
+    error.raw_os_error() == Some(code)
+}
+
+// Copies from to to without following symlinks, which are recreated as
+// links. A copy that fails partway is removed again rather than left behind.
+fn copy_recursive(from: &Path, to: &Path) -> Result<(), io::Error> {
+    if fs::symlink_metadata(to).is_ok() {
+        return Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("{} already exists", to.display())));
+    }
+
+    let result = copy_entry(from, to);
+    if result.is_err() {
+        let _ = remove_any(to);
+    }
+    result
+}
+
+fn copy_entry(from: &Path, to: &Path) -> Result<(), io::Error> {
+    let file_type = fs::symlink_metadata(from)?.file_type();
+
+    if file_type.is_symlink() {
+        copy_link(from, to)
+    } else if file_type.is_dir() {
+        fs::create_dir(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            copy_entry(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        Ok(())
+    } else {
+        fs::copy(from, to).map(|_| ())
+    }
+}
+
+#[cfg(unix)]
+fn copy_link(from: &Path, to: &Path) -> Result<(), io::Error> {
+    std::os::unix::fs::symlink(fs::read_link(from)?, to)
+}
+
+// The target is kept as written, so a relative link still resolves relative
+// to its new location; whether it's a directory link comes from the original
+#[cfg(windows)]
+fn copy_link(from: &Path, to: &Path) -> Result<(), io::Error> {
+    let target = fs::read_link(from)?;
+    if fs::metadata(from).is_ok_and(|metadata| metadata.is_dir()) {
+        std::os::windows::fs::symlink_dir(target, to)
+    } else {
+        std::os::windows::fs::symlink_file(target, to)
+    }
+}
+
+// Removes a file, link or whole directory; a link is removed, not its target
+fn remove_any(path: &Path) -> Result<(), io::Error> {
+    let file_type = fs::symlink_metadata(path)?.file_type();
+    if file_type.is_dir() {
+        fs::remove_dir_all(path)
+    } else if cfg!(windows) && file_type.is_symlink() && path.is_dir() {
+        // Windows directory links are removed like directories
+        fs::remove_dir(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+// On case-insensitive filesystems "readme.md" and "README.md" resolve to the
+// same file, so the destination looks like it already exists
+fn is_case_only_rename(from: &Path, to: &Path) -> bool {
+    let from_text = from.to_string_lossy();
+    let to_text = to.to_string_lossy();
+
+    from_text != to_text
+        && from_text.to_lowercase() == to_text.to_lowercase()
+        && matches!(
+            (fs::canonicalize(from), fs::canonicalize(to)),
+            (Ok(a), Ok(b)) if a == b
+        )
+}
+
+// Renaming straight to the new case is a no-op (or an error) on those
+// filesystems, so go through a temporary name in the same directory
+fn rename_via_temporary(from: &Path, to: &Path) -> Result<(), String> {
+    let name = from.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let parent = from.parent().unwrap_or(Path::new("."));
+
+    let temporary = (0..)
+        .map(|attempt| parent.join(format!(".{}.rename-{}-{}", name, std::process::id(), attempt)))
+        .find(|candidate| fs::symlink_metadata(candidate).is_err())
+        .expect("an unused temporary name");
+
+    fs::rename(from, &temporary).map_err(|e| native::describe_error(from, &e))?;
+    if let Err(e) = fs::rename(&temporary, to) {
+        // Put the original back rather than leaving the temporary name behind
+        let _ = fs::rename(&temporary, from);
+        return Err(e.to_string());
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn create_symlink(original: String, link: String) -> Result<(), String> {
+    let original = Path::new(&original);
+    let link = Path::new(&link);
+    if fs::symlink_metadata(link).is_ok() {
+        return Err(format!("{} already exists", link.display()));
+    }
+
+    symlink(original, link).map_err(|e| symlink_error(&e))
+}
+
+#[tauri::command]
+pub async fn create_hardlink(original: String, link: String) -> Result<(), String> {
+    if fs::symlink_metadata(&link).is_ok() {
+        return Err(format!("{} already exists", link));
+    }
+
+    fs::hard_link(&original, &link).map_err(|e| e.to_string())
+}
+
+#[cfg(unix)]
+fn symlink(original: &Path, link: &Path) -> Result<(), io::Error> {
+    std::os::unix::fs::symlink(original, link)
+}
+
+// Windows distinguishes file and directory links at creation time
+#[cfg(windows)]
+fn symlink(original: &Path, link: &Path) -> Result<(), io::Error> {
+    if original.is_dir() {
+        std::os::windows::fs::symlink_dir(original, link)
+    } else {
+        std::os::windows::fs::symlink_file(original, link)
+    }
+}
+
+fn symlink_error(error: &io::Error) -> String {
+    // ERROR_PRIVILEGE_NOT_HELD
+    if cfg!(windows) && error.raw_os_error() == Some(1314) {
+        return "PermissionDenied: creating symbolic links on Windows needs administrator rights or Developer Mode (Settings > For developers)".to_string();
+    }
+
+    error.to_string()
+}
+
+// Writes every (path, content) pair, creating parent directories as needed.
+// Each write is atomic and independent, so one failure doesn't stop the rest.
+#[tauri::command]
+pub async fn write_files(entries: Vec<(String, String)>) -> Result<Vec<WriteResult>, String> {
+    Ok(entries
+        .into_iter()
+        .map(|(path, content)| {
+            let result = Path::new(&path)
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|_| write_atomic(Path::new(&path), content.as_bytes()));
+
+            WriteResult {
+                ok: result.is_ok(),
+                error: result.err().map(|e| e.to_string()),
+                path,
+            }
+        })
+        .collect())
+}
+
+// Writes to a temporary file next to the target and renames it into place, so
+// readers never see a half-written file. An existing file's permissions are kept.
+pub fn write_atomic(path: &Path, bytes: &[u8]) -> Result<(), io::Error> {
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let temporary = path.with_file_name(format!(
+        ".{}.tmp-{}-{}",
+        name,
+        std::process::id(),
+        NEXT_TEMP_ID.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    let result = (|| {
+        let mut file = fs::File::create(&temporary)?;
+        file.write_all(bytes)?;
+        file.sync_all()?;
+        if let Ok(metadata) = fs::metadata(path) {
+            fs::set_permissions(&temporary, metadata.permissions())?;
+        }
+        fs::rename(&temporary, path)
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temporary);
+    }
+    result
+}
+
+#[tauri::command]
+pub async fn ensure_parent_dir(path: String) -> Result<(), String> {
+    ensure_parent(Path::new(&path))
+}
+
+// Creates any missing directories above path (but not path itself), with a
+// clear error when one of them already exists as a file
+pub fn ensure_parent(path: &Path) -> Result<(), String> {
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => return Ok(()),
+    };
+
+    if let Some(blocker) = parent.ancestors().find(|a| a.exists() && !a.is_dir()) {
+        return Err(format!(
+            "Cannot create {}: {} exists and is not a directory",
+            parent.display(),
+            blocker.display()
+        ));
+    }
+
+    fs::create_dir_all(parent).map_err(|e| e.to_string())
+}
+
+// Removes a directory only if it's empty. A non-empty directory fails with an
+// error starting "NotEmpty:" so the UI can offer a recursive delete instead.
+#[tauri::command]
+pub async fn remove_empty_directory(path: String) -> Result<(), String> {
+    let dir = Path::new(&path);
+    if !dir.is_dir() {
+        return Err(format!("{} is not a directory", dir.display()));
+    }
+
+    fs::remove_dir(dir).map_err(|e| {
+        if is_not_empty(&e) {
+            format!("NotEmpty: {} is not empty", dir.display())
+        } else {
+            native::describe_error(dir, &e)
+        }
+    })
+}
+
+fn is_not_empty(error: &io::Error) -> bool {
+    // Some Unix systems report EEXIST instead of ENOTEMPTY
+    #[cfg(unix)]
+    let codes = [libc::ENOTEMPTY, libc::EEXIST];
+    // ERROR_DIR_NOT_EMPTY
+    #[cfg(windows)]
+    let codes = [145];
+
+    error.raw_os_error().is_some_and(|code| codes.contains(&code))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn copy_recreates_links_instead_of_following_them() {
+        let base = std::env::temp_dir().join(format!("copy-links-test-{}", std::process::id()));
+        fs::create_dir_all(base.join("from").join("sub")).unwrap();
+        fs::write(base.join("from").join("sub").join("a.txt"), "a").unwrap();
+        // A cycle, which would recurse forever if followed
+        std::os::unix::fs::symlink("..", base.join("from").join("sub").join("up")).unwrap();
+
+        let result = copy_recursive(&base.join("from"), &base.join("to"));
+        let link = fs::read_link(base.join("to").join("sub").join("up"));
+        let copied = fs::read_to_string(base.join("to").join("sub").join("a.txt"));
+        fs::remove_dir_all(&base).unwrap();
+
+        result.unwrap();
+        assert_eq!(link.unwrap(), Path::new(".."));
+        assert_eq!(copied.unwrap(), "a");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn failed_copy_removes_the_partial_destination() {
+        let base = std::env::temp_dir().join(format!("copy-partial-test-{}", std::process::id()));
+        fs::create_dir_all(base.join("from")).unwrap();
+        fs::write(base.join("from").join("a.txt"), "a").unwrap();
+        // fs::copy refuses anything that isn't a regular file
+        let _socket = std::os::unix::net::UnixListener::bind(base.join("from").join("b.sock")).unwrap();
+
+        let result = copy_recursive(&base.join("from"), &base.join("to"));
+        let leftover = base.join("to").exists();
+        fs::remove_dir_all(&base).unwrap();
+
+        assert!(result.is_err());
+        assert!(!leftover);
+    }
+
+    #[test]
+    fn year_month_handles_leap_days_and_year_ends() {
+        assert_eq!(year_month(0), (1970, 1));
+        assert_eq!(year_month(-1), (1969, 12));
+        assert_eq!(year_month(951_782_400), (2000, 2));
+        assert_eq!(year_month(1_709_251_199), (2024, 2));
+        assert_eq!(year_month(1_709_251_200), (2024, 3));
+        assert_eq!(year_month(1_735_689_599), (2024, 12));
+        assert!(current_year_month().0 >= 2024);
+    }
+
+    #[test]
+    fn unique_path_numbers_taken_names() {
+        let dir = std::env::temp_dir().join(format!("unique-path-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("notes.md"), "").unwrap();
+        fs::write(dir.join("notes (2).md"), "").unwrap();
+        fs::write(dir.join(".env"), "").unwrap();
+
+        let free = unique_path(&dir, "todo.md");
+        let notes = unique_path(&dir, "notes.md");
+        let env = unique_path(&dir, ".env");
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(free, dir.join("todo.md"));
+        assert_eq!(notes, dir.join("notes (3).md"));
+        assert_eq!(env, dir.join(".env (2)"));
+    }
+}
@@ -0,0 +1,95 @@
+use git2::{ErrorCode, Repository, StatusOptions};
+use serde::Serialize;
+use std::path::Path;
+
+#[derive(Debug, Default, Serialize)]
+pub struct RepoInfo {
+    pub is_repo: bool,
+    // The working tree root, or the git directory itself for a bare repo
+    pub root: Option<String>,
+    // None when HEAD is detached
+    pub branch: Option<String>,
+    pub detached: bool,
+    pub is_submodule: bool,
+    pub is_worktree: bool,
+    pub is_bare: bool,
+    // Staged, unstaged or untracked changes; None when it can't be checked
+    pub has_changes: Option<bool>,
+}
+
+// Everything the header bar shows about the repository containing path. Being
+// outside any repository is reported with is_repo = false, not as an error.
+#[tauri::command]
+pub async fn git_repo_info(path: String) -> Result<RepoInfo, String> {
+    let repo = match Repository::discover(&path) {
+        Ok(repo) => repo,
+        Err(e) if e.code() == ErrorCode::NotFound => return Ok(RepoInfo::default()),
+        Err(e) => return Err(e.message().to_string()),
+    };
+
+    let root = repo.workdir().unwrap_or_else(|| repo.path());
+    let (branch, detached) = current_branch(&repo);
+
+    Ok(RepoInfo {
+        is_repo: true,
+        root: Some(root.to_string_lossy().trim_end_matches(['/', '\\']).to_string()),
+        branch,
+        detached,
+        is_submodule: repo.workdir().is_some_and(is_submodule),
+        is_worktree: repo.is_worktree(),
+        is_bare: repo.is_bare(),
+        has_changes: has_changes(&repo),
+    })
+}
+
+fn current_branch(repo: &Repository) -> (Option<String>, bool) {
+    match repo.head() {
+        Ok(head) if head.is_branch() => (head.shorthand().ok().map(str::to_string), false),
+        Ok(_) => (None, true),
+        // A fresh repository has no commits, but HEAD still names the branch
+        // the first commit will go on
+        Err(_) => {
+            let branch = repo.find_reference("HEAD").ok().and_then(|head| {
+                head.symbolic_target()
+                    .ok()
+                    .flatten()
+                    .map(|target| target.trim_start_matches("refs/heads/").to_string())
+            });
+            (branch, false)
+        }
+    }
+}
+
+// A submodule's working tree is registered in the repository above it
+fn is_submodule(workdir: &Path) -> bool {
+    let parent = match workdir.parent().map(Repository::discover) {
+        Some(Ok(parent)) => parent,
+        _ => return false,
+    };
+    let parent_root = match parent.workdir() {
+        Some(root) => root,
+        None => return false,
+    };
+
+    let relative = match workdir.strip_prefix(parent_root) {
+        Ok(relative) => relative,
+        Err(_) => return false,
+    };
+    parent
+        .submodules()
+        .map(|submodules| submodules.iter().any(|s| s.path() == relative))
+        .unwrap_or(false)
+}
+
+fn has_changes(repo: &Repository) -> Option<bool> {
+    if repo.is_bare() {
+        return None;
+    }
+
+    let mut options = StatusOptions::new();
+    options
+        .include_untracked(true)
+        .include_ignored(false)
+        .exclude_submodules(true);
+    repo.statuses(Some(&mut options)).ok().map(|statuses| !statuses.is_empty())
+}
@@ -0,0 +1,141 @@
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use crate::operations::{self, Operations};
+use crate::walk;
+
+// How much of each end of the file quick_fingerprint looks at
+const FINGERPRINT_EDGE_BYTES: u64 = 64 * 1024;
+
+// Exact SHA-256 of the whole file
+#[tauri::command]
+pub async fn compute_file_hash(path: String) -> Result<String, String> {
+    hash_file(Path::new(&path)).map_err(|e| e.to_string())
+}
+
+// A fast fingerprint built from the size, mtime and the first and last 64KB.
+// This is a heuristic: an edit in the middle of a large file that keeps its
+// size and mtime goes unnoticed, so use compute_file_hash when it matters.
+#[tauri::command]
+pub async fn quick_fingerprint(path: String) -> Result<String, String> {
+    fingerprint(Path::new(&path)).map_err(|e| e.to_string())
+}
+
+// One SHA-256 over every path under root (minus ignored ones) and the content
+// hash of each file. Entries are sorted before hashing, so the result depends
+// only on what is in the tree, and any added, removed, renamed or edited entry
+// changes it.
+#[tauri::command]
+pub async fn tree_checksum(
+    operations: tauri::State<'_, Operations>,
+    root: String,
+    operation_id: Option<String>,
+) -> Result<String, String> {
+    let operation = operations.begin(operation_id);
+    let root = Path::new(&root);
+    let rules = walk::IgnoreRules::load(root).map_err(|e| e.to_string())?;
+    let mut entries = Vec::new();
+    let mut failure = None;
+
+    walk::walk(root, &mut |path, metadata| {
+        if operation.is_cancelled() {
+            return false;
+        }
+
+        // The walk can't skip a subtree, so check every ignored ancestor too
+        let ignored = path
+            .ancestors()
+            .take_while(|ancestor| *ancestor != root)
+            .any(|ancestor| rules.matches(root, ancestor, ancestor != path || metadata.is_dir()));
+        if ignored {
+            return true;
+        }
+
+        let relative = walk::relative_path(root, path);
+        let entry = if metadata.file_type().is_symlink() {
+            std::fs::read_link(path).map(|target| format!("link {} {}", relative, target.to_string_lossy()))
+        } else if metadata.is_dir() {
+            Ok(format!("dir {}", relative))
+        } else {
+            hash_file(path).map(|hash| format!("file {} {}", relative, hash))
+        };
+
+        match entry {
+            Ok(entry) => entries.push(entry),
+            Err(e) => {
+                failure = Some(format!("{}: {}", path.display(), e));
+                return false;
+            }
+        }
+        true
+    })
+    .map_err(|e| e.to_string())?;
+
+    if operation.is_cancelled() {
+        return Err(operations::CANCELLED.to_string());
+    }
+    if let Some(failure) = failure {
+        return Err(failure);
+    }
+
+    entries.sort();
+    let mut hasher = Sha256::new();
+    for entry in &entries {
+        hasher.update(entry.as_bytes());
+        hasher.update(b"\n");
+    }
+    Ok(to_hex(&hasher.finalize()))
+}
+
+pub fn hash_file(path: &Path) -> Result<String, io::Error> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+
+    Ok(to_hex(&hasher.finalize()))
+}
+
+fn fingerprint(path: &Path) -> Result<String, io::Error> {
+    let mut file = File::open(path)?;
+    let metadata = file.metadata()?;
+    let size = metadata.len();
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+
+    let mut hasher = Sha256::new();
+    hasher.update(size.to_le_bytes());
+    hasher.update(modified.to_le_bytes());
+
+    let mut head = Vec::new();
+    (&mut file).take(FINGERPRINT_EDGE_BYTES).read_to_end(&mut head)?;
+    hasher.update(&head);
+
+    // Small files are already covered entirely by the head
+    if size > FINGERPRINT_EDGE_BYTES {
+        let tail_start = size.saturating_sub(FINGERPRINT_EDGE_BYTES).max(FINGERPRINT_EDGE_BYTES);
+        file.seek(SeekFrom::Start(tail_start))?;
+        let mut tail = Vec::new();
+        file.read_to_end(&mut tail)?;
+        hasher.update(&tail);
+    }
+
+    Ok(to_hex(&hasher.finalize()))
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
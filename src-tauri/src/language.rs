@@ -0,0 +1,113 @@
+use serde::Serialize;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+
+struct Language {
+    id: &'static str,
+    name: &'static str,
+    extensions: &'static [&'static str],
+    // Extensionless file names that are always this language
+    filenames: &'static [&'static str],
+    // Interpreters that identify the language in a shebang line
+    interpreters: &'static [&'static str],
+}
+
+const LANGUAGES: &[Language] = &[
+    Language { id: "rust", name: "Rust", extensions: &["rs"], filenames: &[], interpreters: &[] },
+    Language { id: "typescript", name: "TypeScript", extensions: &["ts", "tsx", "mts", "cts"], filenames: &[], interpreters: &["deno", "ts-node"] },
+    Language { id: "javascript", name: "JavaScript", extensions: &["js", "jsx", "mjs", "cjs"], filenames: &[], interpreters: &["node"] },
+    Language { id: "python", name: "Python", extensions: &["py", "pyi", "pyw"], filenames: &[], interpreters: &["python", "python2", "python3"] },
+    Language { id: "markdown", name: "Markdown", extensions: &["md", "markdown"], filenames: &[], interpreters: &[] },
+    Language { id: "json", name: "JSON", extensions: &["json", "jsonc"], filenames: &[], interpreters: &[] },
+    Language { id: "toml", name: "TOML", extensions: &["toml"], filenames: &[], interpreters: &[] },
+    Language { id: "yaml", name: "YAML", extensions: &["yaml", "yml"], filenames: &[], interpreters: &[] },
+    Language { id: "html", name: "HTML", extensions: &["html", "htm"], filenames: &[], interpreters: &[] },
+    Language { id: "css", name: "CSS", extensions: &["css", "scss", "sass", "less"], filenames: &[], interpreters: &[] },
+    Language { id: "shell", name: "Shell", extensions: &["sh", "bash", "zsh"], filenames: &[], interpreters: &["sh", "bash", "zsh", "dash", "ksh"] },
+    Language { id: "ruby", name: "Ruby", extensions: &["rb"], filenames: &["Gemfile", "Rakefile"], interpreters: &["ruby"] },
+    Language { id: "perl", name: "Perl", extensions: &["pl", "pm"], filenames: &[], interpreters: &["perl"] },
+    Language { id: "go", name: "Go", extensions: &["go"], filenames: &[], interpreters: &[] },
+    Language { id: "java", name: "Java", extensions: &["java"], filenames: &[], interpreters: &[] },
+    Language { id: "c", name: "C", extensions: &["c", "h"], filenames: &[], interpreters: &[] },
+    Language { id: "cpp", name: "C++", extensions: &["cpp", "cc", "cxx", "hpp", "hh"], filenames: &[], interpreters: &[] },
+    Language { id: "sql", name: "SQL", extensions: &["sql"], filenames: &[], interpreters: &[] },
+    Language { id: "xml", name: "XML", extensions: &["xml", "svg"], filenames: &[], interpreters: &[] },
+    Language { id: "dockerfile", name: "Dockerfile", extensions: &["dockerfile"], filenames: &["Dockerfile"], interpreters: &[] },
+    Language { id: "makefile", name: "Makefile", extensions: &["mk"], filenames: &["Makefile", "makefile", "GNUmakefile"], interpreters: &[] },
+    Language { id: "plaintext", name: "Plain Text", extensions: &["txt", "log"], filenames: &[], interpreters: &[] },
+];
+
+#[derive(Debug, Serialize)]
+pub struct LanguageInfo {
+    pub id: String,
+    pub name: String,
+    pub extensions: Vec<String>,
+}
+
+#[tauri::command]
+pub async fn get_language_for_path(path: String) -> Option<String> {
+    detect_language(Path::new(&path)).map(|id| id.to_string())
+}
+
+#[tauri::command]
+pub async fn list_supported_languages() -> Vec<LanguageInfo> {
+    LANGUAGES
+        .iter()
+        .map(|language| LanguageInfo {
+            id: language.id.to_string(),
+            name: language.name.to_string(),
+            extensions: language.extensions.iter().map(|ext| ext.to_string()).collect(),
+        })
+        .collect()
+}
+
+pub fn detect_language(path: &Path) -> Option<&'static str> {
+    let file_name = path.file_name()?.to_string_lossy();
+
+    if let Some(language) = LANGUAGES.iter().find(|l| l.filenames.contains(&file_name.as_ref())) {
+        return Some(language.id);
+    }
+
+    match path.extension() {
+        Some(ext) => {
+            let ext = ext.to_string_lossy().to_lowercase();
+            LANGUAGES
+                .iter()
+                .find(|l| l.extensions.contains(&ext.as_str()))
+                .map(|l| l.id)
+        }
+        None => language_from_shebang(path),
+    }
+}
+
+fn language_from_shebang(path: &Path) -> Option<&'static str> {
+    let file = File::open(path).ok()?;
+    let mut first_line = String::new();
+    BufReader::new(file).take(256).read_line(&mut first_line).ok()?;
+
+    let command = first_line.strip_prefix("#!")?.trim();
+    let mut parts = command.split_whitespace();
+    let mut interpreter = parts.next()?.rsplit('/').next()?;
+
+    // "#!/usr/bin/env python3" names the interpreter as the first argument
+    if interpreter == "env" {
+        interpreter = parts.find(|arg| !arg.starts_with('-'))?;
+    }
+
+    LANGUAGES
+        .iter()
+        .find(|l| l.interpreters.contains(&interpreter))
+        .map(|l| l.id)
+}
+
+// Line comment markers, used for rough comment counts in project stats
+pub fn line_comment_prefixes(id: &str) -> &'static [&'static str] {
+    match id {
+        "rust" | "typescript" | "javascript" | "go" | "java" | "c" | "cpp" | "css" => &["//"],
+        "python" | "shell" | "ruby" | "perl" | "toml" | "yaml" | "dockerfile" | "makefile" => &["#"],
+        "sql" => &["--"],
+        "html" | "xml" | "markdown" => &["<!--"],
+        _ => &[],
+    }
+}
@@ -1,8 +1,46 @@
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
+use tauri::{Emitter, Manager};
 
-#[derive(Debug, Serialize, Deserialize)]
+mod archive;
+mod association;
+mod attributes;
+mod classify;
+mod conflict;
+mod drafts;
+mod editorconfig;
+mod file_ops;
+mod git;
+mod hashing;
+mod language;
+mod live_tree;
+mod native;
+mod operations;
+mod outline;
+mod patch;
+mod paths;
+mod roots;
+mod scaffold;
+mod search;
+mod session;
+mod settings;
+mod snapshot;
+mod stats;
+mod structured;
+mod text;
+mod thumbnail;
+mod tree;
+mod walk;
+mod watch;
+
+// How much of a file inspect_before_open looks at to decide how to open it
+const SNIFF_BYTES: u64 = 8000;
+
+// Binary files up to this size can be shown in the hex viewer
+const MAX_HEX_VIEW_BYTES: u64 = 64 * 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileNode {
     pub name: String,
     pub path: String,
@@ -10,6 +48,16 @@ pub struct FileNode {
     pub children: Option<Vec<FileNode>>,
 }
 
+#[derive(Debug, Serialize)]
+pub struct OpenedFile {
+    pub content: String,
+    pub size: u64,
+    pub modified: Option<u64>,
+    pub encoding: String,
+    pub line_ending: text::LineEnding,
+    pub readonly: bool,
+}
+
 #[tauri::command]
 async fn open_folder_dialog(app: tauri::AppHandle) -> Result<Option<String>, String> {
     use tauri_plugin_dialog::DialogExt;
@@ -19,28 +67,272 @@ async fn open_folder_dialog(app: tauri::AppHandle) -> Result<Option<String>, Str
         .blocking_pick_folder();
 
     match folder {
-        Some(path) => Ok(Some(path.to_string())),
+        Some(path) => {
+            let path = path.to_string();
+            roots::register(&app, std::path::Path::new(&path))?;
+            Ok(Some(path))
+        }
         None => Ok(None),
     }
 }
 
 #[tauri::command]
-async fn read_directory(path: String) -> Result<Vec<FileNode>, String> {
-    read_dir_recursive(&PathBuf::from(&path), true)
+async fn read_directory(path: String, apply_ignore_file: Option<bool>) -> Result<Vec<FileNode>, String> {
+    let root = PathBuf::from(&path);
+    let rules = match apply_ignore_file {
+        Some(true) => Some(walk::IgnoreRules::load(&root).map_err(|e| e.to_string())?),
+        _ => None,
+    };
+
+    read_dir_with_rules(&root, &root, true, rules.as_ref())
         .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-async fn read_file_content(path: String) -> Result<String, String> {
+async fn read_file_content(
+    settings: tauri::State<'_, settings::SettingsState>,
+    path: String,
+) -> Result<String, String> {
+    check_fits_in_memory(&path, &settings.get())?;
     fs::read_to_string(&path).map_err(|e| e.to_string())
 }
 
+// Reads content and metadata from the same open handle so the two can't
+// disagree if the file changes in between
+#[tauri::command]
+async fn open_file(
+    settings: tauri::State<'_, settings::SettingsState>,
+    path: String,
+) -> Result<OpenedFile, String> {
+    let mut file = fs::File::open(&path).map_err(|e| e.to_string())?;
+    let metadata = file.metadata().map_err(|e| e.to_string())?;
+
+    check_read_size(&path, metadata.len(), &settings.get())?;
+
+    let mut bytes = Vec::with_capacity(metadata.len() as usize);
+    std::io::Read::read_to_end(&mut file, &mut bytes).map_err(|e| e.to_string())?;
+    let (content, encoding) = text::decode(&bytes);
+
+    Ok(OpenedFile {
+        line_ending: text::detect_line_ending(content.as_bytes()),
+        content,
+        size: bytes.len() as u64,
+        modified: modified_secs(&metadata),
+        encoding: encoding.to_string(),
+        readonly: metadata.permissions().readonly(),
+    })
+}
+
+#[derive(Debug, Serialize)]
+struct OpenPlan {
+    // "text", "image", "hex", "too-large-stream" or "binary-unsupported"
+    mode: &'static str,
+    size: u64,
+    mime_type: Option<&'static str>,
+    encoding: Option<&'static str>,
+    modified: Option<u64>,
+    readonly: bool,
+}
+
+// Picks the viewer for a file from its size, magic bytes and whether its
+// contents look binary, so the editor can branch on a single call
+#[tauri::command]
+async fn inspect_before_open(
+    settings: tauri::State<'_, settings::SettingsState>,
+    path: String,
+) -> Result<OpenPlan, String> {
+    let file = fs::File::open(&path).map_err(|e| e.to_string())?;
+    let metadata = file.metadata().map_err(|e| e.to_string())?;
+    if metadata.is_dir() {
+        return Err(format!("{} is a directory", path));
+    }
+
+    let mut head = Vec::new();
+    std::io::Read::read_to_end(&mut std::io::Read::take(file, SNIFF_BYTES), &mut head).map_err(|e| e.to_string())?;
+
+    let size = metadata.len();
+    let fits = size <= read_limit(&settings.get());
+    let image = sniff_image_type(&head);
+    let bom = text::detect_bom(&head);
+    let binary = bom.is_none() && text::is_binary(&head);
+
+    let (mode, mime_type, encoding) = if let Some(mime) = image {
+        (if fits { "image" } else { "too-large-stream" }, Some(mime), None)
+    } else if !binary {
+        // The sniffed prefix may end partway through a character
+        let encoding = bom
+            .map(text::Bom::encoding)
+            .unwrap_or_else(|| text::decode(&head[..text::utf8_boundary(&head)]).1);
+        (if fits { "text" } else { "too-large-stream" }, None, Some(encoding))
+    } else if size <= MAX_HEX_VIEW_BYTES {
+        ("hex", Some("application/octet-stream"), None)
+    } else {
+        ("binary-unsupported", Some("application/octet-stream"), None)
+    };
+
+    Ok(OpenPlan {
+        mode,
+        size,
+        mime_type,
+        encoding,
+        modified: modified_secs(&metadata),
+        readonly: metadata.permissions().readonly(),
+    })
+}
+
+// Image formats the viewer can show, recognised by their signature rather
+// than the extension
+pub(crate) fn sniff_image_type(head: &[u8]) -> Option<&'static str> {
+    if head.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if head.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if head.starts_with(b"GIF87a") || head.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if head.len() >= 12 && head.starts_with(b"RIFF") && &head[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else if head.len() >= 14 && head.starts_with(b"BM") && head[6..10] == [0, 0, 0, 0] {
+        Some("image/bmp")
+    } else {
+        None
+    }
+}
+
+#[tauri::command]
+async fn can_read_fully(
+    settings: tauri::State<'_, settings::SettingsState>,
+    path: String,
+) -> Result<bool, String> {
+    let size = fs::metadata(&path).map_err(|e| e.to_string())?.len();
+    Ok(size <= read_limit(&settings.get()))
+}
+
+// Largest file a full read is allowed to load, as a share of available memory
+fn read_limit(settings: &settings::Settings) -> u64 {
+    let mut system = sysinfo::System::new();
+    system.refresh_memory();
+
+    // Some platforms don't report available memory; don't block reads there
+    match system.available_memory() {
+        0 => u64::MAX,
+        available => (available as f64 * settings.max_read_memory_fraction) as u64,
+    }
+}
+
+pub(crate) fn check_fits_in_memory(path: &str, settings: &settings::Settings) -> Result<(), String> {
+    let size = fs::metadata(path).map_err(|e| e.to_string())?.len();
+    check_read_size(path, size, settings)
+}
+
+fn check_read_size(path: &str, size: u64, settings: &settings::Settings) -> Result<(), String> {
+    let limit = read_limit(settings);
+
+    if size > limit {
+        return Err(format!(
+            "TooLarge: {} is {} bytes but full reads are limited to {} bytes; open it with a streaming or paged reader instead",
+            path, size, limit
+        ));
+    }
+
+    Ok(())
+}
+
 #[tauri::command]
 async fn read_image_as_data_url(path: String) -> Result<String, String> {
     let bytes = fs::read(&path).map_err(|e| e.to_string())?;
+    Ok(encode_data_url(image_mime_type(&path, &bytes), &bytes))
+}
+
+#[derive(Clone, Serialize)]
+struct ImageChunk {
+    operation_id: String,
+    index: usize,
+    data: String,
+    bytes_read: u64,
+    total_bytes: u64,
+}
+
+#[derive(Clone, Serialize)]
+struct ImageComplete {
+    operation_id: String,
+    mime_type: String,
+    total_bytes: u64,
+}
+
+// Streams the image as base64 "image-chunk" events followed by an
+// "image-complete" event carrying the MIME type, so large images never need
+// to be held in memory as one string
+#[tauri::command]
+async fn stream_image_data_url(
+    app: tauri::AppHandle,
+    operations: tauri::State<'_, operations::Operations>,
+    path: String,
+    operation_id: String,
+) -> Result<(), String> {
+    use std::io::Read;
+
+    let operation = operations.begin(Some(operation_id.clone()));
+    let mut file = fs::File::open(&path).map_err(|e| e.to_string())?;
+    let total_bytes = file.metadata().map_err(|e| e.to_string())?.len();
+
+    // A multiple of 3 bytes encodes without padding, so the chunks can
+    // simply be concatenated on the other side
+    let mut buffer = vec![0u8; 3 * 64 * 1024];
+    let mut bytes_read = 0;
+    let mut index = 0;
+    let mut mime_type = image_mime_type(&path, &[]);
+
+    loop {
+        if operation.is_cancelled() {
+            return Err(operations::CANCELLED.to_string());
+        }
 
-    // Determine MIME type from extension
-    let mime_type = if path.ends_with(".png") {
+        let mut filled = 0;
+        while filled < buffer.len() {
+            match file.read(&mut buffer[filled..]).map_err(|e| e.to_string())? {
+                0 => break,
+                read => filled += read,
+            }
+        }
+        if filled == 0 {
+            break;
+        }
+
+        if index == 0 {
+            mime_type = image_mime_type(&path, &buffer[..filled]);
+        }
+        bytes_read += filled as u64;
+        let data = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &buffer[..filled]);
+        app.emit("image-chunk", ImageChunk {
+            operation_id: operation_id.clone(),
+            index,
+            data,
+            bytes_read,
+            total_bytes,
+        })
+        .map_err(|e| e.to_string())?;
+        index += 1;
+    }
+
+    app.emit("image-complete", ImageComplete {
+        operation_id,
+        mime_type: mime_type.to_string(),
+        total_bytes: bytes_read,
+    })
+    .map_err(|e| e.to_string())
+}
+
+// Determine MIME type from the file's first bytes, falling back to the
+// extension for formats that can't be recognized that way. Data URLs also
+// cover ICO and AVIF, which the webview shows but the viewer doesn't open.
+fn image_mime_type(path: &str, head: &[u8]) -> &'static str {
+    use image::ImageFormat;
+
+    if let Some(mime_type) = sniff_image_type(head) {
+        mime_type
+    } else if let Ok(format @ (ImageFormat::Ico | ImageFormat::Avif)) = image::guess_format(head) {
+        format.to_mime_type()
+    } else if path.ends_with(".png") {
         "image/png"
     } else if path.ends_with(".jpg") || path.ends_with(".jpeg") {
         "image/jpeg"
@@ -52,33 +344,140 @@ async fn read_image_as_data_url(path: String) -> Result<String, String> {
         "image/bmp"
     } else {
         "application/octet-stream"
-    };
+    }
+}
 
-    let base64 = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &bytes);
-    Ok(format!("data:{};base64,{}", mime_type, base64))
+pub(crate) fn encode_data_url(mime_type: &str, bytes: &[u8]) -> String {
+    let base64 = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, bytes);
+    format!("data:{};base64,{}", mime_type, base64)
 }
 
+// Content is written with the line endings the file already uses, so an edit
+// doesn't turn into a whole-file diff. line_ending can force "lf" or "crlf",
+// or "preserve" to write the content exactly as given.
 #[tauri::command]
-async fn write_file_content(path: String, content: String) -> Result<(), String> {
-    fs::write(&path, content).map_err(|e| e.to_string())
+async fn write_file_content(
+    path: String,
+    content: String,
+    create_parents: Option<bool>,
+    line_ending: Option<String>,
+    bom: Option<String>,
+) -> Result<(), String> {
+    if create_parents.unwrap_or(false) {
+        file_ops::ensure_parent(std::path::Path::new(&path))?;
+    }
+
+    let ending = match line_ending.as_deref() {
+        Some("lf") => Some(text::LineEnding::Lf),
+        Some("crlf") => Some(text::LineEnding::Crlf),
+        Some("preserve") => None,
+        Some(other) => return Err(format!("Unknown line ending: {}", other)),
+        None => Some(target_line_ending(std::path::Path::new(&path))),
+    };
+
+    let content = match ending {
+        Some(ending) => text::normalize_line_endings(&content, ending),
+        None => content,
+    };
+
+    // "preserve" keeps the existing file's BOM and the encoding it marks
+    let bom = match bom.as_deref() {
+        None | Some("preserve") => text::read_bom(std::path::Path::new(&path)),
+        Some("strip") => None,
+        Some("add") => Some(text::Bom::Utf8),
+        Some(other) => return Err(format!("Unknown BOM option: {}", other)),
+    };
+
+    // A BOM already in the content would otherwise end up doubled
+    let content = content.strip_prefix('\u{feff}').unwrap_or(&content);
+    let bytes = match bom {
+        Some(bom) => bom.encode(content),
+        None => content.as_bytes().to_vec(),
+    };
+    fs::write(&path, bytes).map_err(|e| e.to_string())
+}
+
+// What an existing file uses, otherwise .editorconfig's end_of_line, otherwise
+// the platform convention
+fn target_line_ending(path: &std::path::Path) -> text::LineEnding {
+    if let Some(ending) = text::predominant_line_ending(path) {
+        return ending;
+    }
+
+    match editorconfig::properties(path).get("end_of_line").map(String::as_str) {
+        Some("crlf") => text::LineEnding::Crlf,
+        Some("lf") => text::LineEnding::Lf,
+        _ if cfg!(windows) => text::LineEnding::Crlf,
+        _ => text::LineEnding::Lf,
+    }
 }
 
 #[tauri::command]
-async fn get_file_metadata(path: String) -> Result<serde_json::Value, String> {
+async fn get_file_metadata(path: String, include_relative: Option<bool>) -> Result<serde_json::Value, String> {
     let metadata = fs::metadata(&path).map_err(|e| e.to_string())?;
+    let modified = modified_secs(&metadata);
 
-    Ok(serde_json::json!({
+    let mut result = serde_json::json!({
         "is_dir": metadata.is_dir(),
         "is_file": metadata.is_file(),
         "size": metadata.len(),
-        "modified": metadata.modified()
-            .ok()
-            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
-            .map(|d| d.as_secs()),
-    }))
+        "modified": modified,
+    });
+    if include_relative.unwrap_or(false) {
+        result["modified_relative"] = modified.map(relative_time).into();
+    }
+    Ok(result)
+}
+
+#[tauri::command]
+async fn format_relative_time(unix_secs: u64) -> String {
+    relative_time(unix_secs)
+}
+
+// "just now", "5 minutes ago", "3 days ago" and so on. Times in the future,
+// as happens with clock skew between machines, count as just now.
+fn relative_time(unix_secs: u64) -> String {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let elapsed = now.saturating_sub(unix_secs);
+
+    const UNITS: &[(u64, &str)] = &[
+        (365 * 24 * 60 * 60, "year"),
+        (30 * 24 * 60 * 60, "month"),
+        (7 * 24 * 60 * 60, "week"),
+        (24 * 60 * 60, "day"),
+        (60 * 60, "hour"),
+        (60, "minute"),
+    ];
+
+    match UNITS.iter().find(|(secs, _)| elapsed >= *secs) {
+        Some((secs, unit)) => {
+            let count = elapsed / secs;
+            format!("{} {}{} ago", count, unit, if count == 1 { "" } else { "s" })
+        }
+        None => "just now".to_string(),
+    }
 }
 
-fn read_dir_recursive(path: &PathBuf, recursive: bool) -> Result<Vec<FileNode>, std::io::Error> {
+pub(crate) fn modified_secs(metadata: &fs::Metadata) -> Option<u64> {
+    metadata.modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+}
+
+pub(crate) fn read_dir_recursive(path: &PathBuf, recursive: bool) -> Result<Vec<FileNode>, std::io::Error> {
+    read_dir_with_rules(path, path, recursive, None)
+}
+
+fn read_dir_with_rules(
+    path: &PathBuf,
+    root: &std::path::Path,
+    recursive: bool,
+    rules: Option<&walk::IgnoreRules>,
+) -> Result<Vec<FileNode>, std::io::Error> {
     let mut nodes = Vec::new();
 
     if !path.is_dir() {
@@ -93,13 +492,13 @@ fn read_dir_recursive(path: &PathBuf, recursive: bool) -> Result<Vec<FileNode>,
         let name = entry.file_name().to_string_lossy().to_string();
         let is_dir = path.is_dir();
 
-        // Skip hidden files and common directories to ignore
-        if name.starts_with('.') || name == "node_modules" || name == "target" || name == "__pycache__" {
+        // Skip hidden files, common directories to ignore and .treeignore matches
+        if walk::is_ignored(&path) || rules.is_some_and(|rules| rules.matches(root, &path, is_dir)) {
             continue;
         }
 
         let children = if is_dir && recursive {
-            Some(read_dir_recursive(&path, recursive)?)
+            Some(read_dir_with_rules(&path, root, recursive, rules)?)
         } else {
             None
         };
@@ -112,7 +511,12 @@ fn read_dir_recursive(path: &PathBuf, recursive: bool) -> Result<Vec<FileNode>,
         });
     }
 
-    // Sort directories first, then files alphabetically
+    sort_nodes(&mut nodes);
+    Ok(nodes)
+}
+
+// Sort directories first, then files alphabetically
+pub(crate) fn sort_nodes(nodes: &mut [FileNode]) {
     nodes.sort_by(|a, b| {
         match (a.is_dir, b.is_dir) {
             (true, false) => std::cmp::Ordering::Less,
@@ -120,16 +524,20 @@ fn read_dir_recursive(path: &PathBuf, recursive: bool) -> Result<Vec<FileNode>,
             _ => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
         }
     });
-
-    Ok(nodes)
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
+        .manage(operations::Operations::default())
+        .manage(watch::PatternWatches::default())
+        .manage(watch::WatcherManager::default())
+        .manage(roots::RootRegistry::default())
+        .manage(live_tree::LiveTrees::default())
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_shell::init())
+        .on_window_event(native::on_window_event)
         .setup(|app| {
             if cfg!(debug_assertions) {
                 app.handle().plugin(
@@ -138,16 +546,127 @@ pub fn run() {
                         .build(),
                 )?;
             }
+
+            app.manage(settings::SettingsState(std::sync::Mutex::new(settings::load(app.handle()))));
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             open_folder_dialog,
             read_directory,
             read_file_content,
+            open_file,
+            can_read_fully,
             read_image_as_data_url,
+            stream_image_data_url,
             write_file_content,
             get_file_metadata,
+            snapshot::snapshot_tree,
+            snapshot::diff_tree_snapshot,
+            language::get_language_for_path,
+            language::list_supported_languages,
+            scaffold::create_tree,
+            settings::get_settings,
+            settings::update_settings,
+            operations::cancel_operation,
+            tree::find_recent_files,
+            paths::normalize_selection,
+            tree::filter_tree,
+            text::audit_line_endings,
+            attributes::is_hidden,
+            attributes::set_hidden,
+            association::get_file_association,
+            watch::watch_for_pattern,
+            watch::unwatch_pattern,
+            paths::common_root,
+            file_ops::rename_path,
+            text::read_file_capped,
+            tree::list_extensions,
+            attributes::can_write,
+            tree::resolve_glob,
+            roots::register_root,
+            roots::list_roots,
+            roots::get_recent_folders,
+            file_ops::move_paths,
+            hashing::compute_file_hash,
+            hashing::quick_fingerprint,
+            live_tree::start_live_tree,
+            live_tree::get_live_tree,
+            live_tree::stop_live_tree,
+            paths::same_file,
+            file_ops::create_symlink,
+            file_ops::create_hardlink,
+            text::read_file_safe_lines,
+            tree::read_directories_only,
+            tree::read_ignore_rules,
+            file_ops::write_files,
+            file_ops::ensure_parent_dir,
+            stats::project_text_stats,
+            archive::list_archive,
+            archive::read_archive_entry,
+            file_ops::remove_empty_directory,
+            roots::read_relative,
+            native::show_properties,
+            watch::watch_paths,
+            watch::unwatch_session,
+            structured::read_structured,
+            structured::write_structured,
+            text::detect_bom_and_encoding,
+            hashing::tree_checksum,
+            text::read_last_lines,
+            paths::validate_filename,
+            paths::get_path_formats,
+            inspect_before_open,
+            session::save_session,
+            session::load_session,
+            tree::export_tree_ndjson,
+            search::search_in_files,
+            native::which_process_locks,
+            classify::classify_file,
+            classify::classify_directory,
+            drafts::save_draft,
+            drafts::load_draft,
+            drafts::discard_draft,
+            drafts::list_drafts,
+            attributes::get_link_info,
+            native::open_terminal,
+            format_relative_time,
+            git::git_repo_info,
+            patch::apply_patch,
+            text::clean_file,
+            outline::file_outline,
+            native::get_system_theme,
+            paths::normalize_dropped_paths,
+            watch::minimal_watch_roots,
+            text::read_file_records,
+            conflict::find_conflict_markers,
+            conflict::resolve_conflict,
+            thumbnail::generate_thumbnails,
+            file_ops::archive_path,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_keeps_crlf_in_utf16_files() {
+        let path = std::env::temp_dir().join(format!("write-utf16-test-{}.txt", std::process::id()));
+        fs::write(&path, text::Bom::Utf16Le.encode("one\r\ntwo\r\n")).unwrap();
+
+        let result = tauri::async_runtime::block_on(write_file_content(
+            path.to_string_lossy().to_string(),
+            "one\ntwo\nthree\n".to_string(),
+            None,
+            None,
+            None,
+        ));
+
+        let bytes = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        result.unwrap();
+        assert_eq!(bytes, text::Bom::Utf16Le.encode("one\r\ntwo\r\nthree\r\n"));
+    }
+}
@@ -0,0 +1,236 @@
+use notify::event::{DataChange, MetadataKind, ModifyKind, RenameMode};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tauri::Emitter;
+
+use crate::walk;
+use crate::FileNode;
+
+struct LiveTree {
+    nodes: Arc<Mutex<Vec<FileNode>>>,
+    // Kept alive for as long as the tree is; dropping it stops the updates
+    _watcher: RecommendedWatcher,
+}
+
+// Trees kept up to date from the watcher, keyed by the root they were started with
+#[derive(Default)]
+pub struct LiveTrees(Mutex<HashMap<String, LiveTree>>);
+
+#[derive(Clone, Serialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+enum TreePatch {
+    Add { parent: String, node: FileNode },
+    Remove { path: String },
+    Rename { from: String, to: String, node: FileNode },
+}
+
+#[derive(Clone, Serialize)]
+struct TreePatchEvent {
+    root: String,
+    #[serde(flatten)]
+    patch: TreePatch,
+}
+
+// Where a live tree lives on disk. Watcher events may report the canonical
+// form of paths (e.g. /private/var on macOS), so both are kept.
+struct TreeRoot {
+    key: String,
+    path: PathBuf,
+    canonical: PathBuf,
+}
+
+// Builds the tree for root, keeps it updated in Rust state and emits a
+// "tree-patch" event for each node added, removed or renamed
+#[tauri::command]
+pub async fn start_live_tree(
+    app: tauri::AppHandle,
+    trees: tauri::State<'_, LiveTrees>,
+    root: String,
+) -> Result<Vec<FileNode>, String> {
+    let path = PathBuf::from(&root);
+    let tree_root = TreeRoot {
+        key: root.clone(),
+        canonical: fs::canonicalize(&path).map_err(|e| e.to_string())?,
+        path: path.clone(),
+    };
+
+    let nodes = Arc::new(Mutex::new(Vec::new()));
+
+    let watched_nodes = nodes.clone();
+    let mut watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+        if let Ok(event) = result {
+            apply_event(&app, &tree_root, &watched_nodes, event);
+        }
+    })
+    .map_err(|e| e.to_string())?;
+
+    // The watcher starts before the tree is read, so nothing that changes in
+    // between is missed. Its events wait on the lock until the tree is built
+    // and are then reconciled against it.
+    let snapshot = {
+        let mut tree = nodes.lock().unwrap();
+        watcher.watch(&path, RecursiveMode::Recursive).map_err(|e| e.to_string())?;
+        *tree = crate::read_dir_recursive(&path, true).map_err(|e| e.to_string())?;
+        tree.clone()
+    };
+    trees.0.lock().unwrap().insert(root, LiveTree { nodes, _watcher: watcher });
+    Ok(snapshot)
+}
+
+#[tauri::command]
+pub async fn get_live_tree(trees: tauri::State<'_, LiveTrees>, root: String) -> Result<Vec<FileNode>, String> {
+    trees
+        .0
+        .lock()
+        .unwrap()
+        .get(&root)
+        .map(|tree| tree.nodes.lock().unwrap().clone())
+        .ok_or_else(|| format!("No live tree for {}", root))
+}
+
+#[tauri::command]
+pub async fn stop_live_tree(trees: tauri::State<'_, LiveTrees>, root: String) -> Result<bool, String> {
+    Ok(trees.0.lock().unwrap().remove(&root).is_some())
+}
+
+fn apply_event(app: &tauri::AppHandle, root: &TreeRoot, nodes: &Mutex<Vec<FileNode>>, event: notify::Event) {
+    // Content and metadata changes don't affect the tree's shape
+    match event.kind {
+        EventKind::Access(_)
+        | EventKind::Modify(ModifyKind::Data(DataChange::Any | DataChange::Content | DataChange::Size))
+        | EventKind::Modify(ModifyKind::Metadata(MetadataKind::Any | MetadataKind::WriteTime)) => return,
+        _ => {}
+    }
+
+    let mut nodes = nodes.lock().unwrap();
+    let emit = |patch: TreePatch| {
+        let _ = app.emit("tree-patch", TreePatchEvent {
+            root: root.key.clone(),
+            patch,
+        });
+    };
+
+    if let (EventKind::Modify(ModifyKind::Name(RenameMode::Both)), [from, to]) = (event.kind, event.paths.as_slice()) {
+        // Either side may be outside the root or ignored, which makes the
+        // rename a plain add or remove as far as the tree is concerned
+        let removed = relative_components(root, from).and_then(|from| remove_node(&mut nodes, &from));
+        let added = relative_components(root, to).and_then(|to| add_node(&mut nodes, root, &to));
+        match (removed, added) {
+            (Some(from), Some((_, node))) => emit(TreePatch::Rename { from, to: node.path.clone(), node }),
+            (Some(path), None) => emit(TreePatch::Remove { path }),
+            (None, Some((parent, node))) => emit(TreePatch::Add { parent, node }),
+            (None, None) => {}
+        }
+        return;
+    }
+
+    // Every other event is reconciled against the disk, which copes with
+    // platforms that report creates, removes and renames imprecisely
+    for path in &event.paths {
+        let components = match relative_components(root, path) {
+            Some(components) => components,
+            None => continue,
+        };
+        let on_disk = fs::symlink_metadata(node_path(root, &components)).is_ok();
+        let in_tree = components
+            .split_last()
+            .and_then(|(name, parent)| Some(find_children(&mut nodes, parent)?.iter().any(|n| n.name == *name)))
+            .unwrap_or(false);
+
+        if on_disk && !in_tree {
+            if let Some((parent, node)) = add_node(&mut nodes, root, &components) {
+                emit(TreePatch::Add { parent, node });
+            }
+        } else if !on_disk && in_tree {
+            if let Some(path) = remove_node(&mut nodes, &components) {
+                emit(TreePatch::Remove { path });
+            }
+        }
+    }
+}
+
+// Path components below the root, or None for the root itself, paths outside
+// it and anything under an ignored entry
+fn relative_components(root: &TreeRoot, path: &Path) -> Option<Vec<String>> {
+    let relative = path
+        .strip_prefix(&root.canonical)
+        .or_else(|_| path.strip_prefix(&root.path))
+        .ok()?;
+
+    let components: Vec<String> = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect();
+    if components.is_empty() {
+        return None;
+    }
+
+    let mut current = root.path.clone();
+    for component in &components {
+        current.push(component);
+        if walk::is_ignored(&current) {
+            return None;
+        }
+    }
+
+    Some(components)
+}
+
+fn node_path(root: &TreeRoot, components: &[String]) -> PathBuf {
+    components.iter().fold(root.path.clone(), |path, c| path.join(c))
+}
+
+fn find_children<'a>(nodes: &'a mut Vec<FileNode>, components: &[String]) -> Option<&'a mut Vec<FileNode>> {
+    match components.split_first() {
+        None => Some(nodes),
+        Some((first, rest)) => {
+            let node = nodes.iter_mut().find(|n| n.is_dir && n.name == *first)?;
+            find_children(node.children.as_mut()?, rest)
+        }
+    }
+}
+
+// Inserts the node at components, returning its parent's path and the node.
+// Nothing happens if the parent isn't in the tree yet; it will bring its own
+// children along when it's added.
+fn add_node(nodes: &mut Vec<FileNode>, root: &TreeRoot, components: &[String]) -> Option<(String, FileNode)> {
+    let (name, parent_components) = components.split_last()?;
+    let siblings = find_children(nodes, parent_components)?;
+    if siblings.iter().any(|n| n.name == *name) {
+        return None;
+    }
+
+    let parent = node_path(root, parent_components);
+    let path = parent.join(name);
+    fs::symlink_metadata(&path).ok()?;
+    // Follows symlinks, as read_dir_recursive does for the initial tree, so a
+    // linked directory keeps the same shape after an update
+    let is_dir = path.is_dir();
+
+    let node = FileNode {
+        name: name.clone(),
+        path: path.to_string_lossy().to_string(),
+        is_dir,
+        children: if is_dir {
+            Some(crate::read_dir_recursive(&path, true).unwrap_or_default())
+        } else {
+            None
+        },
+    };
+
+    siblings.push(node.clone());
+    crate::sort_nodes(siblings);
+    Some((parent.to_string_lossy().to_string(), node))
+}
+
+// Removes the node at components, returning its path
+fn remove_node(nodes: &mut Vec<FileNode>, components: &[String]) -> Option<String> {
+    let (name, parent_components) = components.split_last()?;
+    let siblings = find_children(nodes, parent_components)?;
+    let index = siblings.iter().position(|n| n.name == *name)?;
+    Some(siblings.remove(index).path)
+}
@@ -0,0 +1,315 @@
+use serde::Serialize;
+use std::io;
+use std::path::Path;
+use std::sync::atomic::{AtomicU8, Ordering};
+use tauri::{Emitter, Manager};
+use tauri_plugin_shell::ShellExt;
+
+use crate::settings::SettingsState;
+
+// Tried in order on Linux when no terminal is configured
+#[cfg(all(unix, not(target_os = "macos")))]
+const LINUX_TERMINALS: &[&str] = &[
+    "x-terminal-emulator",
+    "gnome-terminal",
+    "konsole",
+    "xfce4-terminal",
+    "alacritty",
+    "kitty",
+    "xterm",
+];
+
+// The last theme announced with "system-theme-changed": 0 none yet, 1 light,
+// 2 dark. Every window reports the same OS change, so only the first counts.
+static LAST_THEME: AtomicU8 = AtomicU8::new(0);
+
+#[derive(Debug, Serialize)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub name: String,
+    // Set when the process is a Windows service
+    pub service: Option<String>,
+}
+
+// Opens the OS's own properties / Get Info panel for path and returns None.
+// Linux has no common equivalent, so there the details are returned instead
+// for the app to show itself.
+#[tauri::command]
+pub async fn show_properties(app: tauri::AppHandle, path: String) -> Result<Option<serde_json::Value>, String> {
+    if !Path::new(&path).exists() {
+        return Err(format!("{} does not exist", path));
+    }
+
+    open_properties(&app, &path).await
+}
+
+#[cfg(windows)]
+async fn open_properties(_app: &tauri::AppHandle, path: &str) -> Result<Option<serde_json::Value>, String> {
+    use windows_sys::Win32::UI::Shell::{SHObjectProperties, SHOP_FILEPATH};
+
+    let wide: Vec<u16> = path.encode_utf16().chain(Some(0)).collect();
+    let shown = unsafe { SHObjectProperties(std::ptr::null_mut(), SHOP_FILEPATH as u32, wide.as_ptr(), std::ptr::null()) };
+
+    if shown == 0 {
+        return Err(format!("Could not open the properties dialog for {}", path));
+    }
+    Ok(None)
+}
+
+#[cfg(target_os = "macos")]
+async fn open_properties(app: &tauri::AppHandle, path: &str) -> Result<Option<serde_json::Value>, String> {
+    // The path is passed as an argument rather than spliced into the script
+    let script = r#"on run argv
+    tell application "Finder"
+        activate
+        open information window of (POSIX file (item 1 of argv) as alias)
+    end tell
+end run"#;
+
+    let output = app
+        .shell()
+        .command("osascript")
+        .args(["-e", script, path])
+        .output()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Could not open Get Info for {}: {}",
+            path,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(None)
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+async fn open_properties(_app: &tauri::AppHandle, path: &str) -> Result<Option<serde_json::Value>, String> {
+    use std::os::unix::fs::MetadataExt;
+
+    let metadata = std::fs::symlink_metadata(path).map_err(|e| e.to_string())?;
+    let name = Path::new(path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    Ok(Some(serde_json::json!({
+        "path": path,
+        "name": name,
+        "is_dir": metadata.is_dir(),
+        "is_symlink": metadata.file_type().is_symlink(),
+        "size": metadata.len(),
+        "modified": crate::modified_secs(&metadata),
+        "accessed": metadata.atime(),
+        "changed": metadata.ctime(),
+        "mode": format!("{:o}", metadata.mode() & 0o7777),
+        "uid": metadata.uid(),
+        "gid": metadata.gid(),
+        "readonly": metadata.permissions().readonly(),
+    })))
+}
+
+#[cfg(not(any(windows, unix)))]
+async fn open_properties(_app: &tauri::AppHandle, _path: &str) -> Result<Option<serde_json::Value>, String> {
+    Err("Unsupported: no properties dialog on this platform".to_string())
+}
+
+// Processes holding path open, as reported by the Windows Restart Manager
+#[tauri::command]
+pub async fn which_process_locks(path: String) -> Result<Vec<ProcessInfo>, String> {
+    if !Path::new(&path).exists() {
+        return Err(format!("{} does not exist", path));
+    }
+
+    #[cfg(windows)]
+    return find_lockers(&path);
+
+    #[cfg(not(windows))]
+    Err("Unsupported: finding which processes hold a file open is only available on Windows".to_string())
+}
+
+// The error text for a failed operation on path. Sharing violations on
+// Windows name the processes holding the file, so the user knows what to close.
+pub fn describe_error(path: &Path, error: &io::Error) -> String {
+    #[cfg(windows)]
+    {
+        // ERROR_SHARING_VIOLATION, ERROR_LOCK_VIOLATION
+        if matches!(error.raw_os_error(), Some(32) | Some(33)) {
+            if let Ok(lockers) = find_lockers(&path.to_string_lossy()) {
+                if !lockers.is_empty() {
+                    let names: Vec<&str> = lockers.iter().map(|p| p.name.as_str()).collect();
+                    return format!("InUse: {} is in use by {}", path.display(), names.join(", "));
+                }
+            }
+        }
+    }
+
+    #[cfg(not(windows))]
+    let _ = path;
+    error.to_string()
+}
+
+#[cfg(windows)]
+fn find_lockers(path: &str) -> Result<Vec<ProcessInfo>, String> {
+    use windows_sys::Win32::Foundation::{ERROR_MORE_DATA, ERROR_SUCCESS};
+    use windows_sys::Win32::System::RestartManager::{
+        RmEndSession, RmGetList, RmRegisterResources, RmStartSession, CCH_RM_SESSION_KEY, RM_PROCESS_INFO,
+    };
+
+    let mut session = 0u32;
+    let mut key = [0u16; CCH_RM_SESSION_KEY as usize + 1];
+    let status = unsafe { RmStartSession(&mut session, 0, key.as_mut_ptr()) };
+    if status != ERROR_SUCCESS {
+        return Err(format!("Could not start a Restart Manager session (error {})", status));
+    }
+
+    let result = (|| {
+        let wide: Vec<u16> = path.encode_utf16().chain(Some(0)).collect();
+        let files = [wide.as_ptr()];
+        let status = unsafe {
+            RmRegisterResources(session, 1, files.as_ptr(), 0, std::ptr::null(), 0, std::ptr::null())
+        };
+        if status != ERROR_SUCCESS {
+            return Err(format!("Could not register {} (error {})", path, status));
+        }
+
+        // The list can grow between calls, so retry with whatever size is asked for
+        let mut processes: Vec<RM_PROCESS_INFO> = Vec::new();
+        loop {
+            let mut needed = 0u32;
+            let mut count = processes.len() as u32;
+            let mut reasons = 0u32;
+            let status = unsafe { RmGetList(session, &mut needed, &mut count, processes.as_mut_ptr(), &mut reasons) };
+
+            match status {
+                ERROR_SUCCESS => {
+                    processes.truncate(count as usize);
+                    return Ok(processes);
+                }
+                ERROR_MORE_DATA => processes.resize_with(needed as usize, Default::default),
+                _ => return Err(format!("Could not list processes using {} (error {})", path, status)),
+            }
+        }
+    })();
+
+    unsafe { RmEndSession(session) };
+
+    Ok(result?
+        .iter()
+        .map(|process| {
+            let service = wide_to_string(&process.strServiceShortName);
+            ProcessInfo {
+                pid: process.Process.dwProcessId,
+                name: wide_to_string(&process.strAppName),
+                service: if service.is_empty() { None } else { Some(service) },
+            }
+        })
+        .collect())
+}
+
+#[cfg(windows)]
+fn wide_to_string(wide: &[u16]) -> String {
+    let len = wide.iter().position(|&c| c == 0).unwrap_or(wide.len());
+    String::from_utf16_lossy(&wide[..len])
+}
+
+// Opens a terminal in path, or in its parent directory for a file. The
+// "terminal" setting names a program to use instead of the platform default;
+// it is started with the directory as its working directory.
+#[tauri::command]
+pub async fn open_terminal(app: tauri::AppHandle, path: String) -> Result<(), String> {
+    let path = Path::new(&path);
+    let dir = if path.is_dir() {
+        path
+    } else {
+        path.parent()
+            .filter(|parent| parent.is_dir())
+            .ok_or_else(|| format!("{} does not exist", path.display()))?
+    };
+
+    let configured = app.state::<SettingsState>().get().terminal.filter(|t| !t.trim().is_empty());
+    let (program, args) = match configured {
+        Some(terminal) => {
+            let mut parts = terminal.split_whitespace().map(str::to_string);
+            let program = parts.next().unwrap_or_default();
+            (program, parts.collect())
+        }
+        None => default_terminal(dir)?,
+    };
+
+    app.shell()
+        .command(&program)
+        .args(args)
+        .current_dir(dir)
+        .spawn()
+        .map_err(|e| format!("Could not start terminal {}: {}", program, e))?;
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn default_terminal(dir: &Path) -> Result<(String, Vec<String>), String> {
+    let app = if Path::new("/Applications/iTerm.app").exists() { "iTerm" } else { "Terminal" };
+    Ok(("open".to_string(), vec!["-a".to_string(), app.to_string(), dir.to_string_lossy().to_string()]))
+}
+
+#[cfg(windows)]
+fn default_terminal(dir: &Path) -> Result<(String, Vec<String>), String> {
+    if find_in_path("wt.exe").is_some() {
+        return Ok(("wt.exe".to_string(), vec!["-d".to_string(), dir.to_string_lossy().to_string()]));
+    }
+
+    // "start" gives cmd its own console window instead of inheriting ours
+    Ok(("cmd.exe".to_string(), vec!["/c".to_string(), "start".to_string(), "cmd.exe".to_string()]))
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn default_terminal(_dir: &Path) -> Result<(String, Vec<String>), String> {
+    LINUX_TERMINALS
+        .iter()
+        .find(|terminal| find_in_path(terminal).is_some())
+        .map(|terminal| (terminal.to_string(), Vec::new()))
+        .ok_or_else(|| {
+            format!(
+                "No terminal found (tried {}); set one in the terminal setting",
+                LINUX_TERMINALS.join(", ")
+            )
+        })
+}
+
+#[cfg(not(any(windows, unix)))]
+fn default_terminal(_dir: &Path) -> Result<(String, Vec<String>), String> {
+    Err("Unsupported: no default terminal on this platform; set one in the terminal setting".to_string())
+}
+
+#[cfg(any(windows, all(unix, not(target_os = "macos"))))]
+fn find_in_path(program: &str) -> Option<std::path::PathBuf> {
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        .map(|dir| dir.join(program))
+        .find(|candidate| candidate.is_file())
+}
+
+// "dark" or "light", as the OS currently reports it for this window
+#[tauri::command]
+pub async fn get_system_theme(window: tauri::Window) -> Result<String, String> {
+    window.theme().map(|theme| theme_name(theme).to_string()).map_err(|e| e.to_string())
+}
+
+// Emits "system-theme-changed" with "dark" or "light" when the OS appearance
+// changes
+pub fn on_window_event(window: &tauri::Window, event: &tauri::WindowEvent) {
+    if let tauri::WindowEvent::ThemeChanged(theme) = event {
+        let code = if *theme == tauri::Theme::Dark { 2 } else { 1 };
+        if LAST_THEME.swap(code, Ordering::Relaxed) != code {
+            let _ = window.app_handle().emit("system-theme-changed", theme_name(*theme));
+        }
+    }
+}
+
+// Theme is non-exhaustive; anything that isn't dark is treated as light
+fn theme_name(theme: tauri::Theme) -> &'static str {
+    match theme {
+        tauri::Theme::Dark => "dark",
+        _ => "light",
+    }
+}
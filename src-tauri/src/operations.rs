@@ -0,0 +1,59 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+// Long-running commands register under a caller-chosen operation id so the
+// frontend can cancel them with cancel_operation
+#[derive(Default)]
+pub struct Operations(Mutex<HashMap<String, Arc<AtomicBool>>>);
+
+pub struct Operation<'a> {
+    registry: &'a Operations,
+    id: Option<String>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Operations {
+    pub fn begin(&self, id: Option<String>) -> Operation<'_> {
+        let cancelled = Arc::new(AtomicBool::new(false));
+        if let Some(id) = &id {
+            self.0.lock().unwrap().insert(id.clone(), cancelled.clone());
+        }
+
+        Operation {
+            registry: self,
+            id,
+            cancelled,
+        }
+    }
+}
+
+impl Operation<'_> {
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for Operation<'_> {
+    fn drop(&mut self) {
+        if let Some(id) = &self.id {
+            self.registry.0.lock().unwrap().remove(id);
+        }
+    }
+}
+
+pub const CANCELLED: &str = "Cancelled";
+
+#[tauri::command]
+pub async fn cancel_operation(
+    operations: tauri::State<'_, Operations>,
+    operation_id: String,
+) -> Result<bool, String> {
+    match operations.0.lock().unwrap().get(&operation_id) {
+        Some(cancelled) => {
+            cancelled.store(true, Ordering::Relaxed);
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
@@ -0,0 +1,495 @@
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+use crate::{language, text};
+
+// Outlines are for files someone is reading; larger ones are unlikely to be
+const MAX_OUTLINE_FILE_BYTES: u64 = 10 * 1024 * 1024;
+
+#[derive(Debug, Serialize)]
+pub struct OutlineSymbol {
+    pub name: String,
+    // "function", "struct", "class", "heading", "key" and so on
+    pub kind: &'static str,
+    // Nesting level: the heading level minus one for Markdown, 0 elsewhere
+    pub depth: usize,
+    // 1-based and inclusive
+    pub start_line: usize,
+    pub end_line: usize,
+}
+
+// Top-level symbols found with per-language line heuristics rather than a real
+// parser, so unusual formatting can be missed. Languages without rules give
+// an empty outline.
+#[tauri::command]
+pub async fn file_outline(path: String) -> Result<Vec<OutlineSymbol>, String> {
+    let path = Path::new(&path);
+    let language = match language::detect_language(path) {
+        Some(language) => language,
+        None => return Ok(Vec::new()),
+    };
+
+    if fs::metadata(path).map_err(|e| e.to_string())?.len() > MAX_OUTLINE_FILE_BYTES {
+        return Err(format!("TooLarge: {} is too large to outline", path.display()));
+    }
+    let bytes = fs::read(path).map_err(|e| e.to_string())?;
+    if text::is_binary(&bytes) {
+        return Ok(Vec::new());
+    }
+    let (content, _) = text::decode(&bytes);
+    let lines: Vec<&str> = content.lines().collect();
+
+    Ok(match language {
+        "markdown" => markdown_outline(&lines),
+        "rust" => brace_outline(&lines, rust_symbol),
+        "javascript" | "typescript" => brace_outline(&lines, script_symbol),
+        "go" => brace_outline(&lines, go_symbol),
+        "python" => python_outline(&lines),
+        "json" => json_outline(&lines),
+        _ => Vec::new(),
+    })
+}
+
+// ATX headings ("## Title"), each running until the next heading at the same
+// or a higher level. Fenced code blocks are skipped so "# comment" in a shell
+// snippet isn't a heading.
+fn markdown_outline(lines: &[&str]) -> Vec<OutlineSymbol> {
+    let mut headings: Vec<(usize, usize, String)> = Vec::new();
+    let mut fence: Option<&str> = None;
+
+    for (index, line) in lines.iter().enumerate() {
+        let trimmed = line.trim_start();
+        if let Some(marker) = ["```", "~~~"].into_iter().find(|m| trimmed.starts_with(m)) {
+            fence = match fence {
+                Some(open) if open == marker => None,
+                None => Some(marker),
+                other => other,
+            };
+            continue;
+        }
+        if fence.is_some() {
+            continue;
+        }
+
+        let level = trimmed.chars().take_while(|&c| c == '#').count();
+        let rest = &trimmed[level..];
+        if (1..=6).contains(&level) && (rest.is_empty() || rest.starts_with([' ', '\t'])) {
+            let title = rest.trim().trim_end_matches('#').trim_end().to_string();
+            headings.push((index, level, title));
+        }
+    }
+
+    headings
+        .iter()
+        .enumerate()
+        .map(|(i, (start, level, title))| {
+            let end = headings[i + 1..]
+                .iter()
+                .find(|(_, next_level, _)| next_level <= level)
+                .map(|(next, _, _)| *next)
+                .unwrap_or(lines.len());
+            OutlineSymbol {
+                name: title.clone(),
+                kind: "heading",
+                depth: level - 1,
+                start_line: start + 1,
+                end_line: end.max(start + 1),
+            }
+        })
+        .collect()
+}
+
+// Languages with braces: a declaration starting in the first column runs to
+// its matching close brace, or to the ";" that ends it
+fn brace_outline(lines: &[&str], symbol: fn(&str) -> Option<(&'static str, String)>) -> Vec<OutlineSymbol> {
+    let mut symbols = Vec::new();
+    let mut index = 0;
+
+    while index < lines.len() {
+        let line = lines[index];
+        let declaration = if line.starts_with(char::is_whitespace) { None } else { symbol(line) };
+
+        match declaration {
+            Some((kind, name)) => {
+                let end = block_end(lines, index);
+                symbols.push(OutlineSymbol {
+                    name,
+                    kind,
+                    depth: 0,
+                    start_line: index + 1,
+                    end_line: end + 1,
+                });
+                index = end + 1;
+            }
+            None => index += 1,
+        }
+    }
+
+    symbols
+}
+
+// Index of the line closing the block that starts on lines[start]. Braces in
+// strings and line comments are ignored.
+fn block_end(lines: &[&str], start: usize) -> usize {
+    let mut depth = 0usize;
+    let mut opened = false;
+
+    for (index, line) in lines.iter().enumerate().skip(start) {
+        let mut chars = line.chars().peekable();
+        let mut quote: Option<char> = None;
+
+        while let Some(c) = chars.next() {
+            match (quote, c) {
+                (Some(_), '\\') => {
+                    chars.next();
+                }
+                (Some(q), c) if c == q => quote = None,
+                (Some(_), _) => {}
+                (None, '"' | '`') => quote = Some(c),
+                (None, '/') if chars.peek() == Some(&'/') => break,
+                (None, '{') => {
+                    depth += 1;
+                    opened = true;
+                }
+                (None, '}') => {
+                    depth = depth.saturating_sub(1);
+                    if opened && depth == 0 {
+                        return index;
+                    }
+                }
+                (None, ';') if !opened => return index,
+                _ => {}
+            }
+        }
+    }
+
+    lines.len().saturating_sub(1)
+}
+
+// The first identifier in text
+fn identifier(text: &str) -> Option<String> {
+    let name: String = text
+        .trim_start()
+        .chars()
+        .take_while(|c| c.is_alphanumeric() || *c == '_' || *c == '$')
+        .collect();
+    if name.is_empty() {
+        None
+    } else {
+        Some(name)
+    }
+}
+
+// Strips leading words that don't change what is being declared
+fn strip_modifiers<'a>(mut line: &'a str, modifiers: &[&str]) -> &'a str {
+    loop {
+        let before = line;
+        for modifier in modifiers {
+            if let Some(rest) = line.strip_prefix(modifier) {
+                if rest.starts_with(char::is_whitespace) {
+                    line = rest.trim_start();
+                }
+            }
+        }
+        // pub(crate), pub(super), pub(in path)
+        if let Some(rest) = line.strip_prefix("pub(") {
+            if let Some(close) = rest.find(')') {
+                line = rest[close + 1..].trim_start();
+            }
+        }
+        if line == before {
+            return line;
+        }
+    }
+}
+
+fn rust_symbol(line: &str) -> Option<(&'static str, String)> {
+    let line = strip_modifiers(line, &["pub", "async", "unsafe", "extern", "default"]);
+    // extern "C" fn
+    let line = line.strip_prefix("\"C\"").map(str::trim_start).unwrap_or(line);
+
+    // "const fn" is a function, anything else after const is a constant
+    if let Some(rest) = line.strip_prefix("const ") {
+        let rest = strip_modifiers(rest.trim_start(), &["async", "unsafe"]);
+        return match rest.strip_prefix("fn ") {
+            Some(function) => identifier(function).map(|name| ("function", name)),
+            None => identifier(rest).map(|name| ("const", name)),
+        };
+    }
+
+    const ITEMS: &[(&str, &str)] = &[
+        ("fn", "function"),
+        ("struct", "struct"),
+        ("enum", "enum"),
+        ("trait", "trait"),
+        ("union", "union"),
+        ("mod", "module"),
+        ("type", "type"),
+        ("static", "static"),
+        ("macro_rules!", "macro"),
+    ];
+    for (keyword, kind) in ITEMS {
+        if let Some(rest) = line.strip_prefix(keyword) {
+            if *keyword == "macro_rules!" || rest.starts_with(char::is_whitespace) {
+                return identifier(rest).map(|name| (*kind, name));
+            }
+        }
+    }
+
+    // "impl<T> Display for Wrapper<T>" is named after everything up to the block
+    if let Some(rest) = line.strip_prefix("impl") {
+        if rest.starts_with([' ', '<']) {
+            let name = rest.split('{').next().unwrap_or(rest).trim();
+            let name = name.split(" where").next().unwrap_or(name).trim();
+            return Some(("impl", name.to_string()));
+        }
+    }
+
+    None
+}
+
+fn script_symbol(line: &str) -> Option<(&'static str, String)> {
+    let line = strip_modifiers(line, &["export", "default", "declare", "abstract", "async"]);
+
+    const ITEMS: &[(&str, &str)] = &[
+        ("function*", "function"),
+        ("function", "function"),
+        ("class", "class"),
+        ("interface", "interface"),
+        ("enum", "enum"),
+        ("type", "type"),
+        ("namespace", "namespace"),
+    ];
+    for (keyword, kind) in ITEMS {
+        if let Some(rest) = line.strip_prefix(keyword) {
+            if rest.starts_with(char::is_whitespace) {
+                if let Some(name) = identifier(rest) {
+                    return Some((*kind, name));
+                }
+            }
+        }
+    }
+
+    // const handler = (...) => { ... } and const handler = function ...
+    for keyword in ["const", "let", "var"] {
+        if let Some(rest) = line.strip_prefix(keyword).filter(|r| r.starts_with(char::is_whitespace)) {
+            let name = identifier(rest)?;
+            let value = rest.split_once('=')?.1.trim_start();
+            let value = value.strip_prefix("async").map(str::trim_start).unwrap_or(value);
+            if value.starts_with("function") || value.starts_with('(') && value.contains("=>") {
+                return Some(("function", name));
+            }
+        }
+    }
+
+    None
+}
+
+fn go_symbol(line: &str) -> Option<(&'static str, String)> {
+    if let Some(rest) = line.strip_prefix("func ") {
+        // Methods: "func (s *Server) Start() {" is named "Server.Start"
+        if let Some(receiver) = rest.strip_prefix('(') {
+            let (receiver, after) = receiver.split_once(')')?;
+            let receiver_type = receiver.split_whitespace().last()?.trim_start_matches('*');
+            return identifier(after).map(|name| ("method", format!("{}.{}", receiver_type, name)));
+        }
+        return identifier(rest).map(|name| ("function", name));
+    }
+
+    let rest = line.strip_prefix("type ")?;
+    let name = identifier(rest)?;
+    let kind = if rest.contains(" struct") {
+        "struct"
+    } else if rest.contains(" interface") {
+        "interface"
+    } else {
+        "type"
+    };
+    Some((kind, name))
+}
+
+// def and class in the first column, each running to the last non-blank line
+// before the next statement in the first column. Decorators belong to the
+// definition below them.
+fn python_outline(lines: &[&str]) -> Vec<OutlineSymbol> {
+    let mut symbols = Vec::new();
+    let mut decorator_start = None;
+
+    for (index, line) in lines.iter().enumerate() {
+        if line.starts_with('@') {
+            decorator_start.get_or_insert(index);
+            continue;
+        }
+
+        let definition = line.strip_prefix("async ").unwrap_or(line);
+        let symbol = if let Some(rest) = definition.strip_prefix("def ") {
+            identifier(rest).map(|name| ("function", name))
+        } else if let Some(rest) = definition.strip_prefix("class ") {
+            identifier(rest).map(|name| ("class", name))
+        } else {
+            None
+        };
+        let start = decorator_start.take().unwrap_or(index);
+
+        if let Some((kind, name)) = symbol {
+            let next_statement = lines[index + 1..]
+                .iter()
+                .position(|l| !l.is_empty() && !l.starts_with(char::is_whitespace) && !l.starts_with('#'))
+                .map(|offset| index + 1 + offset)
+                .unwrap_or(lines.len());
+            let end = (index..next_statement)
+                .rev()
+                .find(|&i| !lines[i].trim().is_empty())
+                .unwrap_or(index);
+
+            symbols.push(OutlineSymbol {
+                name,
+                kind,
+                depth: 0,
+                start_line: start + 1,
+                end_line: end + 1,
+            });
+        }
+    }
+
+    symbols
+}
+
+// Keys of the top-level object, each running to the end of its value
+fn json_outline(lines: &[&str]) -> Vec<OutlineSymbol> {
+    let mut symbols: Vec<OutlineSymbol> = Vec::new();
+    let mut depth = 0usize;
+    let mut last_string: Option<(String, usize)> = None;
+    let mut in_value = false;
+
+    for (index, line) in lines.iter().enumerate() {
+        let mut chars = line.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => {
+                    let mut value = String::new();
+                    while let Some(c) = chars.next() {
+                        match c {
+                            '\\' => value.extend(chars.next()),
+                            '"' => break,
+                            c => value.push(c),
+                        }
+                    }
+                    last_string = Some((value, index));
+                }
+                ':' if depth == 1 => {
+                    if let Some((key, line)) = last_string.take() {
+                        symbols.push(OutlineSymbol {
+                            name: key,
+                            kind: "key",
+                            depth: 0,
+                            start_line: line + 1,
+                            end_line: line + 1,
+                        });
+                        in_value = true;
+                    }
+                }
+                '{' | '[' => depth += 1,
+                '}' | ']' => {
+                    depth = depth.saturating_sub(1);
+                    if depth == 0 {
+                        in_value = false;
+                    }
+                }
+                ',' if depth == 1 => in_value = false,
+                _ => {}
+            }
+
+            if in_value {
+                if let Some(symbol) = symbols.last_mut() {
+                    symbol.end_line = index + 1;
+                }
+            }
+        }
+    }
+
+    symbols
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(symbols: Vec<OutlineSymbol>) -> Vec<(String, &'static str, usize, usize, usize)> {
+        symbols
+            .into_iter()
+            .map(|s| (s.name, s.kind, s.depth, s.start_line, s.end_line))
+            .collect()
+    }
+
+    fn symbol(name: &str, kind: &'static str, depth: usize, start: usize, end: usize) -> (String, &'static str, usize, usize, usize) {
+        (name.to_string(), kind, depth, start, end)
+    }
+
+    #[test]
+    fn rust_items_run_to_their_closing_brace_or_semicolon() {
+        let source = "\
+use std::fs;
+
+pub(crate) struct Point {
+    x: i32,
+}
+
+impl Display for Wrapper where T: Debug {
+    fn fmt() { let s = \"}\"; }
+}
+const LIMIT: u32 = 3;
+pub const fn half() -> u32 { 1 }";
+        let lines: Vec<&str> = source.lines().collect();
+        assert_eq!(summary(brace_outline(&lines, rust_symbol)), [
+            symbol("Point", "struct", 0, 3, 5),
+            symbol("Display for Wrapper", "impl", 0, 7, 9),
+            symbol("LIMIT", "const", 0, 10, 10),
+            symbol("half", "function", 0, 11, 11),
+        ]);
+    }
+
+    #[test]
+    fn script_and_go_declarations() {
+        let script: Vec<&str> = "export const handler = async (req) => {\n  return 1;\n};\nclass Foo {}".lines().collect();
+        assert_eq!(summary(brace_outline(&script, script_symbol)), [
+            symbol("handler", "function", 0, 1, 3),
+            symbol("Foo", "class", 0, 4, 4),
+        ]);
+
+        let go: Vec<&str> = "func (s *Server) Start() {\n}\ntype Config struct {\n}".lines().collect();
+        assert_eq!(summary(brace_outline(&go, go_symbol)), [
+            symbol("Server.Start", "method", 0, 1, 2),
+            symbol("Config", "struct", 0, 3, 4),
+        ]);
+    }
+
+    #[test]
+    fn markdown_headings_skip_code_fences() {
+        let lines: Vec<&str> = "# Title\nintro\n## Part\n```\n# not a heading\n```\n## Next\ntext".lines().collect();
+        assert_eq!(summary(markdown_outline(&lines)), [
+            symbol("Title", "heading", 0, 1, 8),
+            symbol("Part", "heading", 1, 3, 6),
+            symbol("Next", "heading", 1, 7, 8),
+        ]);
+    }
+
+    #[test]
+    fn python_definitions_include_decorators() {
+        let lines: Vec<&str> = "import os\n\n@cache\ndef load(path):\n    return path\n\nclass Store:\n    pass".lines().collect();
+        assert_eq!(summary(python_outline(&lines)), [
+            symbol("load", "function", 0, 3, 5),
+            symbol("Store", "class", 0, 7, 8),
+        ]);
+    }
+
+    #[test]
+    fn json_top_level_keys_span_their_values() {
+        let lines: Vec<&str> = "{\n  \"name\": \"x\",\n  \"scripts\": {\n    \"a\": \"b\"\n  }\n}".lines().collect();
+        assert_eq!(summary(json_outline(&lines)), [
+            symbol("name", "key", 0, 2, 2),
+            symbol("scripts", "key", 0, 3, 5),
+        ]);
+    }
+}
@@ -0,0 +1,262 @@
+use std::fs;
+use std::path::Path;
+
+use crate::{file_ops, text};
+
+#[derive(Debug)]
+struct Hunk {
+    header: String,
+    old_start: usize,
+    // Context and removed lines, in order
+    old: Vec<String>,
+    // Context and added lines, in order
+    new: Vec<String>,
+    // Set by "\ No newline at end of file" after the new side's last line
+    new_missing_newline: bool,
+    // Lines still to come on each side, from the header's counts
+    old_remaining: usize,
+    new_remaining: usize,
+}
+
+// Applies a unified diff for a single file. Each hunk must match the file
+// exactly, though it may have moved up or down (as when earlier hunks were
+// already applied by hand); otherwise nothing is written and the error names
+// the hunk. dry_run only checks that the patch applies.
+#[tauri::command]
+pub async fn apply_patch(path: String, unified_diff: String, dry_run: Option<bool>) -> Result<(), String> {
+    let bytes = fs::read(&path).map_err(|e| e.to_string())?;
+    if text::is_binary(&bytes) {
+        return Err(format!("{} is a binary file", path));
+    }
+    let content = String::from_utf8(bytes).map_err(|_| format!("{} is not valid UTF-8", path))?;
+
+    let hunks = parse(&unified_diff)?;
+    let patched = apply(&content, &hunks)?;
+
+    if dry_run.unwrap_or(false) || patched == content {
+        return Ok(());
+    }
+    file_ops::write_atomic(Path::new(&path), patched.as_bytes()).map_err(|e| e.to_string())
+}
+
+fn parse(diff: &str) -> Result<Vec<Hunk>, String> {
+    let mut hunks: Vec<Hunk> = Vec::new();
+    let mut files = 0;
+    let mut last_side = ' ';
+
+    for line in diff.lines() {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        let in_hunk = hunks.last().is_some_and(|h| h.old_remaining > 0 || h.new_remaining > 0);
+
+        if !in_hunk && line.starts_with("+++ ") {
+            files += 1;
+            if files > 1 {
+                return Err("Unsupported: the patch changes more than one file".to_string());
+            }
+            continue;
+        }
+        if let Some(header) = line.strip_prefix("@@ ").filter(|_| !in_hunk) {
+            hunks.push(parse_header(line, header)?);
+            continue;
+        }
+
+        let hunk = match hunks.last_mut() {
+            Some(hunk) => hunk,
+            // "diff", "index" and "---" lines before the first hunk
+            None => continue,
+        };
+
+        // The marker only ever follows the line it describes
+        if line.starts_with('\\') {
+            if last_side != '-' {
+                hunk.new_missing_newline = true;
+            }
+            continue;
+        }
+        // Lines past the header's counts (such as an email signature) aren't
+        // part of the hunk
+        if hunk.old_remaining == 0 && hunk.new_remaining == 0 {
+            continue;
+        }
+
+        // Some tools drop the space from empty context lines
+        let (side, text) = match line.chars().next() {
+            Some(side @ (' ' | '-' | '+')) => (side, line[1..].to_string()),
+            None => (' ', String::new()),
+            _ => return Err(format!("Invalid line in hunk {}: {}", hunk.header, line)),
+        };
+        if side != '+' {
+            hunk.old.push(text.clone());
+            hunk.old_remaining = hunk.old_remaining.checked_sub(1).ok_or_else(|| too_long(hunk))?;
+        }
+        if side != '-' {
+            hunk.new.push(text);
+            hunk.new_remaining = hunk.new_remaining.checked_sub(1).ok_or_else(|| too_long(hunk))?;
+        }
+        last_side = side;
+    }
+
+    if let Some(hunk) = hunks.iter().find(|h| h.old_remaining > 0 || h.new_remaining > 0) {
+        return Err(format!("Hunk {} is shorter than its header says", hunk.header));
+    }
+    if hunks.is_empty() {
+        return Err("The patch has no hunks".to_string());
+    }
+    Ok(hunks)
+}
+
+fn too_long(hunk: &Hunk) -> String {
+    format!("Hunk {} is longer than its header says", hunk.header)
+}
+
+// "@@ -12,5 +12,6 @@ fn context"; a range without a count covers one line
+fn parse_header(line: &str, header: &str) -> Result<Hunk, String> {
+    let invalid = || format!("Invalid hunk header: {}", line);
+    let mut ranges = header.split_whitespace();
+    let old_range = ranges.next().and_then(|r| r.strip_prefix('-')).ok_or_else(invalid)?;
+    let new_range = ranges.next().and_then(|r| r.strip_prefix('+')).ok_or_else(invalid)?;
+
+    let parse_range = |range: &str| -> Option<(usize, usize)> {
+        let mut parts = range.split(',');
+        let start = parts.next()?.parse().ok()?;
+        let count = match parts.next() {
+            Some(count) => count.parse().ok()?,
+            None => 1,
+        };
+        Some((start, count))
+    };
+    let (old_start, old_count) = parse_range(old_range).ok_or_else(invalid)?;
+    let (_, new_count) = parse_range(new_range).ok_or_else(invalid)?;
+
+    Ok(Hunk {
+        header: line.to_string(),
+        old_start,
+        old: Vec::new(),
+        new: Vec::new(),
+        new_missing_newline: false,
+        old_remaining: old_count,
+        new_remaining: new_count,
+    })
+}
+
+fn apply(content: &str, hunks: &[Hunk]) -> Result<String, String> {
+    let ending = match text::detect_line_ending(content.as_bytes()) {
+        text::LineEnding::Crlf => "\r\n",
+        _ => "\n",
+    };
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    let mut final_newline = content.ends_with('\n');
+
+    // Where the next hunk may start, and how far hunks have shifted so far
+    let mut floor = 0;
+    let mut shift: isize = 0;
+
+    for (index, hunk) in hunks.iter().enumerate() {
+        // A hunk with no old lines inserts after line old_start rather than
+        // replacing from it
+        let old_index = if hunk.old.is_empty() { hunk.old_start } else { hunk.old_start.max(1) - 1 };
+        let expected = (old_index as isize + shift).max(floor as isize) as usize;
+        let start = find_block(&lines, &hunk.old, expected, floor).ok_or_else(|| {
+            format!(
+                "HunkMismatch: hunk {} ({}) doesn't match the file near line {}",
+                index + 1,
+                hunk.header,
+                expected + 1
+            )
+        })?;
+
+        let reaches_end = start + hunk.old.len() == lines.len();
+        lines.splice(start..start + hunk.old.len(), hunk.new.iter().cloned());
+        if reaches_end {
+            final_newline = !hunk.new_missing_newline;
+        }
+
+        floor = start + hunk.new.len();
+        shift += hunk.new.len() as isize - hunk.old.len() as isize;
+    }
+
+    let mut patched = lines.join(ending);
+    if final_newline && !lines.is_empty() {
+        patched.push_str(ending);
+    }
+    Ok(patched)
+}
+
+// The start of the first exact match for block at or after floor, trying
+// positions closest to expected first
+fn find_block(lines: &[String], block: &[String], expected: usize, floor: usize) -> Option<usize> {
+    let matches_at = |start: usize| start + block.len() <= lines.len() && lines[start..start + block.len()] == *block;
+    let last = lines.len().saturating_sub(block.len());
+
+    (0..=lines.len().max(expected)).find_map(|distance| {
+        let after = expected + distance;
+        let before = expected.checked_sub(distance).filter(|&start| start >= floor);
+        [Some(after).filter(|&start| start <= last), before]
+            .into_iter()
+            .flatten()
+            .find(|&start| matches_at(start))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIFF: &str = "\
+--- a/notes.txt
++++ b/notes.txt
+@@ -1,3 +1,3 @@
+ one
+-two
++TWO
+ three
+@@ -5 +5,2 @@
+ five
++six
+";
+
+    #[test]
+    fn parse_reads_hunk_ranges_and_lines() {
+        let hunks = parse(DIFF).unwrap();
+        assert_eq!(hunks.len(), 2);
+        assert_eq!(hunks[0].old_start, 1);
+        assert_eq!(hunks[0].old, ["one", "two", "three"]);
+        assert_eq!(hunks[0].new, ["one", "TWO", "three"]);
+        assert_eq!(hunks[1].old, ["five"]);
+        assert_eq!(hunks[1].new, ["five", "six"]);
+    }
+
+    #[test]
+    fn parse_rejects_miscounted_and_multi_file_patches() {
+        assert!(parse("@@ -1,2 +1,2 @@\n one\n").unwrap_err().contains("shorter than its header says"));
+        assert!(parse("+++ b/a\n@@ -1 +1 @@\n-a\n+b\n+++ b/b\n").unwrap_err().starts_with("Unsupported:"));
+        assert_eq!(parse("just text\n").unwrap_err(), "The patch has no hunks");
+    }
+
+    #[test]
+    fn apply_finds_hunks_that_moved() {
+        let hunks = parse(DIFF).unwrap();
+        assert_eq!(apply("one\ntwo\nthree\nfour\nfive\n", &hunks).unwrap(), "one\nTWO\nthree\nfour\nfive\nsix\n");
+        // Two extra lines at the top push both hunks down
+        assert_eq!(
+            apply("x\ny\none\ntwo\nthree\nfour\nfive\n", &hunks).unwrap(),
+            "x\ny\none\nTWO\nthree\nfour\nfive\nsix\n"
+        );
+    }
+
+    #[test]
+    fn apply_keeps_crlf_and_reports_mismatches() {
+        let hunks = parse(DIFF).unwrap();
+        assert_eq!(
+            apply("one\r\ntwo\r\nthree\r\nfour\r\nfive\r\n", &hunks).unwrap(),
+            "one\r\nTWO\r\nthree\r\nfour\r\nfive\r\nsix\r\n"
+        );
+        assert!(apply("one\nzwei\nthree\n", &hunks).unwrap_err().starts_with("HunkMismatch: hunk 1"));
+    }
+
+    #[test]
+    fn apply_honours_no_newline_at_end_of_file() {
+        let hunks = parse("@@ -1 +1 @@\n-a\n+b\n\\ No newline at end of file\n").unwrap();
+        assert_eq!(apply("a\n", &hunks).unwrap(), "b");
+    }
+}
@@ -0,0 +1,401 @@
+use std::fs;
+use serde::Serialize;
+use std::path::{Component, Path, PathBuf};
+
+// Most filesystems cap a single name at 255 bytes (UTF-16 units on Windows)
+const MAX_NAME_LENGTH: usize = 255;
+
+#[cfg(windows)]
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9", "LPT1",
+    "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+#[derive(Debug, Serialize)]
+pub struct PathFormats {
+    pub absolute: String,
+    // None when path is on a different drive from root
+    pub relative: Option<String>,
+    pub uri: String,
+    pub posix: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FilenameValidation {
+    pub valid: bool,
+    // Why the name isn't valid, suitable for showing next to the input
+    pub reason: Option<String>,
+    pub exists: bool,
+}
+
+#[tauri::command]
+pub async fn normalize_selection(paths: Vec<String>) -> Vec<String> {
+    normalize(paths)
+}
+
+// Drops duplicates (including symlink aliases of the same file) and any path
+// that lives inside another selected directory, keeping the caller's spelling
+// of the first occurrence. Batch operations should run selections through
+// this before touching the disk.
+pub fn normalize(paths: Vec<String>) -> Vec<String> {
+    // Paths that don't exist can't be resolved, so compare them as given
+    let resolved: Vec<(String, PathBuf)> = paths
+        .into_iter()
+        .map(|path| {
+            let canonical = fs::canonicalize(&path).unwrap_or_else(|_| PathBuf::from(&path));
+            (path, canonical)
+        })
+        .collect();
+
+    let mut kept: Vec<&(String, PathBuf)> = Vec::new();
+    for (index, entry) in resolved.iter().enumerate() {
+        let (_, canonical) = entry;
+
+        let duplicate = resolved[..index].iter().any(|(_, earlier)| earlier == canonical);
+        let nested = resolved
+            .iter()
+            .any(|(_, other)| other != canonical && canonical.starts_with(other));
+
+        if !duplicate && !nested {
+            kept.push(entry);
+        }
+    }
+
+    kept.into_iter().map(|(path, _)| path.clone()).collect()
+}
+
+// Deepest directory containing every path. Paths that share nothing (such as
+// different drives on Windows) have no common root.
+#[tauri::command]
+pub async fn common_root(paths: Vec<String>) -> Option<String> {
+    let mut paths = paths.iter().map(Path::new);
+    let first = paths.next()?;
+    let mut common: Vec<Component> = first.components().collect();
+
+    for path in paths {
+        let shared = common
+            .iter()
+            .zip(path.components())
+            .take_while(|(a, b)| same_component(a, b))
+            .count();
+        common.truncate(shared);
+    }
+
+    // A bare drive prefix like "C:" without its root is not a usable directory
+    if common.is_empty() || matches!(common.as_slice(), [Component::Prefix(_)]) {
+        return None;
+    }
+
+    let mut root: PathBuf = common.iter().collect();
+    if root.is_file() {
+        root.pop();
+    }
+    Some(root.to_string_lossy().to_string())
+}
+
+// Windows paths are case-insensitive, so "C:\Users" and "c:\users" agree
+fn same_component(a: &Component, b: &Component) -> bool {
+    if cfg!(windows) {
+        a.as_os_str().to_string_lossy().to_lowercase() == b.as_os_str().to_string_lossy().to_lowercase()
+    } else {
+        a == b
+    }
+}
+
+// True when both paths refer to the same file on disk (device and inode on
+// Unix, volume and file index on Windows), so links, case differences and
+// "./" spellings all compare correctly
+#[tauri::command]
+pub async fn same_file(a: String, b: String) -> Result<bool, String> {
+    same_file::is_same_file(&a, &b).map_err(|e| e.to_string())
+}
+
+// Checks a proposed name against the current platform's naming rules and
+// whether something by that name is already in in_dir. Nothing is created.
+#[tauri::command]
+pub async fn validate_filename(name: String, in_dir: String) -> Result<FilenameValidation, String> {
+    let dir = Path::new(&in_dir);
+    if !dir.is_dir() {
+        return Err(format!("{} is not a directory", in_dir));
+    }
+
+    let reason = filename_problem(&name);
+    // The filesystem itself decides whether names differing only in case clash
+    let exists = reason.is_none() && fs::symlink_metadata(dir.join(&name)).is_ok();
+
+    Ok(FilenameValidation {
+        valid: reason.is_none(),
+        reason,
+        exists,
+    })
+}
+
+fn filename_problem(name: &str) -> Option<String> {
+    if name.is_empty() {
+        return Some("Name can't be empty".to_string());
+    }
+    if name == "." || name == ".." {
+        return Some(format!("\"{}\" is reserved", name));
+    }
+    if name.contains('\0') {
+        return Some("Name can't contain a NUL character".to_string());
+    }
+    if name.contains('/') {
+        return Some("Name can't contain \"/\"".to_string());
+    }
+
+    #[cfg(windows)]
+    {
+        if let Some(c) = name.chars().find(|c| matches!(c, '<' | '>' | ':' | '"' | '\\' | '|' | '?' | '*') || c.is_control()) {
+            return Some(if c.is_control() {
+                "Name can't contain control characters".to_string()
+            } else {
+                format!("Name can't contain \"{}\"", c)
+            });
+        }
+        if name.ends_with(' ') || name.ends_with('.') {
+            return Some("Name can't end with a space or a period".to_string());
+        }
+
+        // Reserved device names apply whatever the extension, e.g. "nul.txt"
+        let stem = name.split('.').next().unwrap_or(name).trim_end();
+        if RESERVED_NAMES.iter().any(|reserved| reserved.eq_ignore_ascii_case(stem)) {
+            return Some(format!("\"{}\" is a reserved name on Windows", stem));
+        }
+        if name.encode_utf16().count() > MAX_NAME_LENGTH {
+            return Some(format!("Name is longer than {} characters", MAX_NAME_LENGTH));
+        }
+    }
+
+    #[cfg(not(windows))]
+    if name.len() > MAX_NAME_LENGTH {
+        return Some(format!("Name is longer than {} bytes", MAX_NAME_LENGTH));
+    }
+
+    None
+}
+
+// The forms of path offered by the copy-path menu. A relative path is taken
+// as relative to root. Everything is worked out lexically, so symlinks are
+// kept as spelled and the path doesn't need to exist.
+#[tauri::command]
+pub async fn get_path_formats(root: String, path: String) -> Result<PathFormats, String> {
+    let root = absolute(Path::new(&root))?;
+    let absolute = absolute(&root.join(&path))?;
+
+    let posix = absolute.to_string_lossy().replace('\\', "/");
+    Ok(PathFormats {
+        relative: relative_to(&root, &absolute),
+        uri: file_uri(&posix),
+        posix,
+        absolute: absolute.to_string_lossy().to_string(),
+    })
+}
+
+// Joins onto the current directory if needed and resolves "." and ".."
+// without touching the disk
+fn absolute(path: &Path) -> Result<PathBuf, String> {
+    let joined = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir().map_err(|e| e.to_string())?.join(path)
+    };
+
+    let mut clean = PathBuf::new();
+    for component in joined.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                clean.pop();
+            }
+            other => clean.push(other),
+        }
+    }
+    Ok(clean)
+}
+
+fn relative_to(root: &Path, path: &Path) -> Option<String> {
+    let root: Vec<Component> = root.components().collect();
+    let path: Vec<Component> = path.components().collect();
+    let shared = root
+        .iter()
+        .zip(&path)
+        .take_while(|(a, b)| same_component(a, b))
+        .count();
+
+    // Nothing shared beyond the root directory means different drives
+    if shared == 0 || (matches!(root.first(), Some(Component::Prefix(_))) && shared < 2) {
+        return None;
+    }
+
+    let parts: Vec<String> = std::iter::repeat("..".to_string())
+        .take(root.len() - shared)
+        .chain(path[shared..].iter().map(|c| c.as_os_str().to_string_lossy().to_string()))
+        .collect();
+    Some(if parts.is_empty() { ".".to_string() } else { parts.join("/") })
+}
+
+// file:// URI for a forward-slash path: "/a b" becomes file:///a%20b,
+// "C:/a" file:///C:/a and "//server/share" file://server/share
+fn file_uri(posix: &str) -> String {
+    let encoded: String = posix
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect();
+
+    if let Some(unc) = encoded.strip_prefix("//") {
+        format!("file://{}", unc)
+    } else if encoded.starts_with('/') {
+        format!("file://{}", encoded)
+    } else {
+        format!("file:///{}", encoded)
+    }
+}
+
+// Turns drag-and-drop payloads into canonical filesystem paths. Entries may be
+// plain paths, file:// URIs (percent-encoded or not) or text/uri-list blocks
+// with several per line; anything that doesn't resolve to an existing path
+// is dropped.
+#[tauri::command]
+pub async fn normalize_dropped_paths(raw: Vec<String>) -> Result<Vec<String>, String> {
+    let mut resolved: Vec<String> = Vec::new();
+
+    let items = raw
+        .iter()
+        .flat_map(|item| item.lines())
+        .map(str::trim)
+        .filter(|item| !item.is_empty() && !item.starts_with('#'));
+    for item in items {
+        let candidates = match item.get(..7).filter(|scheme| scheme.eq_ignore_ascii_case("file://")) {
+            Some(_) => vec![uri_to_path(&item[7..])],
+            // Already a path, though some sources percent-encode those too
+            None => vec![Some(item.to_string()), percent_decode(item)],
+        };
+
+        let canonical = candidates
+            .into_iter()
+            .flatten()
+            .find_map(|candidate| fs::canonicalize(candidate).ok())
+            .map(|path| strip_verbatim_prefix(&path.to_string_lossy()));
+        if let Some(path) = canonical {
+            if !resolved.contains(&path) {
+                resolved.push(path);
+            }
+        }
+    }
+
+    Ok(resolved)
+}
+
+// The part of a file URI after "file://": an optional host, then the path
+fn uri_to_path(rest: &str) -> Option<String> {
+    // Query strings and fragments aren't part of the path
+    let rest = rest.split(['?', '#']).next().unwrap_or(rest);
+    let (host, path) = match rest.find('/') {
+        Some(slash) => (&rest[..slash], &rest[slash..]),
+        None => (rest, "/"),
+    };
+    let path = percent_decode(path)?;
+
+    if !host.is_empty() && !host.eq_ignore_ascii_case("localhost") {
+        // file://server/share/file is a UNC path, only meaningful on Windows
+        return if cfg!(windows) {
+            Some(format!("\\\\{}{}", host, path.replace('/', "\\")))
+        } else {
+            None
+        };
+    }
+
+    // file:///C:/Users -> C:\Users
+    if cfg!(windows) {
+        let bytes = path.as_bytes();
+        if bytes.len() >= 3 && bytes[0] == b'/' && bytes[1].is_ascii_alphabetic() && bytes[2] == b':' {
+            return Some(path[1..].replace('/', "\\"));
+        }
+    }
+    Some(path)
+}
+
+// None when the escapes don't decode to UTF-8
+fn percent_decode(text: &str) -> Option<String> {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| text.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8(decoded).ok()
+}
+
+// canonicalize on Windows returns \\?\C:\... which other tools and the
+// frontend don't expect; drop the prefix when the path is an ordinary one
+fn strip_verbatim_prefix(path: &str) -> String {
+    match path.strip_prefix(r"\\?\") {
+        Some(rest) if rest.len() >= 2 && rest.as_bytes()[1] == b':' => rest.to_string(),
+        Some(rest) if rest.starts_with("UNC\\") => format!(r"\\{}", &rest[4..]),
+        _ => path.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filename_problems_name_the_offending_character() {
+        assert_eq!(filename_problem("a\0b").as_deref(), Some("Name can't contain a NUL character"));
+        assert_eq!(filename_problem("a/b").as_deref(), Some("Name can't contain \"/\""));
+        assert_eq!(filename_problem("notes.md"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn relative_to_walks_up_and_down() {
+        assert_eq!(relative_to(Path::new("/work/app"), Path::new("/work/app/src/main.rs")).as_deref(), Some("src/main.rs"));
+        assert_eq!(relative_to(Path::new("/work/app"), Path::new("/work/lib/a.rs")).as_deref(), Some("../lib/a.rs"));
+        assert_eq!(relative_to(Path::new("/work/app"), Path::new("/work/app")).as_deref(), Some("."));
+    }
+
+    #[test]
+    fn file_uri_encodes_and_handles_drives_and_shares() {
+        assert_eq!(file_uri("/a b/c#1.txt"), "file:///a%20b/c%231.txt");
+        assert_eq!(file_uri("C:/Users/me"), "file:///C:/Users/me");
+        assert_eq!(file_uri("//server/share/x"), "file://server/share/x");
+        assert_eq!(file_uri("/caf\u{e9}"), "file:///caf%C3%A9");
+    }
+
+    #[test]
+    fn percent_decode_leaves_stray_percents_and_rejects_bad_utf8() {
+        assert_eq!(percent_decode("a%20b%C3%A9").as_deref(), Some("a b\u{e9}"));
+        assert_eq!(percent_decode("100%").as_deref(), Some("100%"));
+        assert_eq!(percent_decode("%zz").as_deref(), Some("%zz"));
+        assert_eq!(percent_decode("%FF"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn uri_to_path_drops_host_query_and_fragment() {
+        assert_eq!(uri_to_path("/tmp/a%20b.txt").as_deref(), Some("/tmp/a b.txt"));
+        assert_eq!(uri_to_path("localhost/tmp/x?raw=1#top").as_deref(), Some("/tmp/x"));
+        // A UNC share only means something on Windows
+        assert_eq!(uri_to_path("server/share/x"), None);
+    }
+}
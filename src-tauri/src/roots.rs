@@ -0,0 +1,144 @@
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{Emitter, Manager};
+
+use crate::settings::SettingsState;
+
+// How many folders the recent list keeps
+const MAX_RECENT_FOLDERS: usize = 10;
+
+// Folders the user has opened as workspace roots, stored canonicalized
+#[derive(Default)]
+pub struct RootRegistry(Mutex<Vec<PathBuf>>);
+
+impl RootRegistry {
+    pub fn register(&self, root: PathBuf) {
+        let mut roots = self.0.lock().unwrap();
+        if !roots.contains(&root) {
+            roots.push(root);
+        }
+    }
+
+    pub fn roots(&self) -> Vec<PathBuf> {
+        self.0.lock().unwrap().clone()
+    }
+
+    // The registered root matching path, in canonical form
+    pub fn find(&self, path: &Path) -> Option<PathBuf> {
+        let canonical = fs::canonicalize(path).ok()?;
+        self.0.lock().unwrap().iter().find(|root| **root == canonical).cloned()
+    }
+}
+
+#[derive(Clone, Serialize)]
+struct RootRelocated {
+    from: String,
+    to: String,
+}
+
+#[tauri::command]
+pub async fn register_root(app: tauri::AppHandle, path: String) -> Result<(), String> {
+    register(&app, Path::new(&path))
+}
+
+#[tauri::command]
+pub async fn list_roots(registry: tauri::State<'_, RootRegistry>) -> Result<Vec<String>, String> {
+    Ok(registry
+        .roots()
+        .iter()
+        .map(|root| root.to_string_lossy().to_string())
+        .collect())
+}
+
+#[tauri::command]
+pub async fn get_recent_folders(settings: tauri::State<'_, SettingsState>) -> Result<Vec<String>, String> {
+    Ok(settings.get().recent_folders)
+}
+
+// Reads rel_path inside a registered root. The joined path is canonicalized
+// before the containment check, so ".." components and symlinks pointing
+// outside the root are rejected rather than followed.
+#[tauri::command]
+pub async fn read_relative(
+    registry: tauri::State<'_, RootRegistry>,
+    settings: tauri::State<'_, SettingsState>,
+    root: String,
+    rel_path: String,
+) -> Result<String, String> {
+    let root = registry
+        .find(Path::new(&root))
+        .ok_or_else(|| format!("{} is not an opened folder", root))?;
+
+    let path = fs::canonicalize(root.join(&rel_path)).map_err(|e| e.to_string())?;
+    if !path.starts_with(&root) {
+        return Err(format!("{} is outside {}", rel_path, root.display()));
+    }
+
+    let path = path.to_string_lossy();
+    crate::check_fits_in_memory(&path, &settings.get())?;
+    fs::read_to_string(path.as_ref()).map_err(|e| e.to_string())
+}
+
+// Registers an opened folder as a root and moves it to the front of the
+// recent list
+pub fn register(app: &tauri::AppHandle, path: &Path) -> Result<(), String> {
+    let root = fs::canonicalize(path).map_err(|e| e.to_string())?;
+    if !root.is_dir() {
+        return Err(format!("{} is not a directory", root.display()));
+    }
+
+    app.state::<RootRegistry>().register(root.clone());
+
+    let root = root.to_string_lossy().to_string();
+    app.state::<SettingsState>().update(app, |settings| {
+        settings.recent_folders.retain(|folder| *folder != root);
+        settings.recent_folders.insert(0, root);
+        settings.recent_folders.truncate(MAX_RECENT_FOLDERS);
+    })
+}
+
+// Called after `from` has been moved to `to`. Any root that was `from` or lived
+// inside it is rewritten, in both the registry and the recent list, and a
+// "root-relocated" event is emitted for each so the UI can follow.
+pub fn relocate(app: &tauri::AppHandle, from: &Path, to: &Path) {
+    // `from` no longer exists, so canonicalize its parent instead
+    let from = match (from.parent().and_then(|p| fs::canonicalize(p).ok()), from.file_name()) {
+        (Some(parent), Some(name)) => parent.join(name),
+        _ => return,
+    };
+    let to = match fs::canonicalize(to) {
+        Ok(to) => to,
+        Err(_) => return,
+    };
+
+    let relocate_path = |path: &Path| path.strip_prefix(&from).ok().map(|rest| to.join(rest));
+
+    let mut relocated = Vec::new();
+    {
+        let registry = app.state::<RootRegistry>();
+        let mut roots = registry.0.lock().unwrap();
+        for root in roots.iter_mut() {
+            if let Some(new_root) = relocate_path(root) {
+                relocated.push(RootRelocated {
+                    from: root.to_string_lossy().to_string(),
+                    to: new_root.to_string_lossy().to_string(),
+                });
+                *root = new_root;
+            }
+        }
+    }
+
+    let _ = app.state::<SettingsState>().update(app, |settings| {
+        for folder in settings.recent_folders.iter_mut() {
+            if let Some(new_folder) = relocate_path(Path::new(folder)) {
+                *folder = new_folder.to_string_lossy().to_string();
+            }
+        }
+    });
+
+    for event in relocated {
+        let _ = app.emit("root-relocated", event);
+    }
+}
@@ -0,0 +1,150 @@
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Everything create_tree has done so far, so a failure can be undone
+#[derive(Default)]
+struct Changes {
+    created_dirs: Vec<PathBuf>,
+    created_files: Vec<PathBuf>,
+    overwritten: Vec<(PathBuf, Vec<u8>)>,
+}
+
+// The spec is a JSON object mapping names to either a nested object (a
+// directory), a string (a file with those contents) or null (an empty file):
+//
+//   { "src": { "main.rs": "fn main() {}" }, "README.md": null, "assets": {} }
+#[tauri::command]
+pub async fn create_tree(root: String, spec: Value, overwrite: Option<bool>) -> Result<Vec<String>, String> {
+    let root = PathBuf::from(&root);
+    let overwrite = overwrite.unwrap_or(false);
+
+    // Validate the whole spec up front so most problems are caught before
+    // anything touches the disk
+    validate(&root, &spec, overwrite)?;
+
+    let mut changes = Changes::default();
+    let created = create_missing_dirs(&root, &mut changes).and_then(|_| create_entries(&root, &spec, &mut changes));
+    if let Err(e) = created {
+        rollback(changes);
+        return Err(e);
+    }
+
+    Ok(changes
+        .created_dirs
+        .iter()
+        .chain(changes.created_files.iter())
+        .chain(changes.overwritten.iter().map(|(path, _)| path))
+        .map(|path| path.to_string_lossy().to_string())
+        .collect())
+}
+
+fn spec_entries(spec: &Value) -> Result<&serde_json::Map<String, Value>, String> {
+    spec.as_object()
+        .ok_or_else(|| "Tree spec must be a JSON object of names to entries".to_string())
+}
+
+fn validate(dir: &Path, spec: &Value, overwrite: bool) -> Result<(), String> {
+    if dir.exists() && !dir.is_dir() {
+        return Err(format!("{} exists and is not a directory", dir.display()));
+    }
+
+    for (name, entry) in spec_entries(spec)? {
+        if name.is_empty() || name == "." || name == ".." || name.contains(['/', '\\']) {
+            return Err(format!("Invalid name in tree spec: {:?}", name));
+        }
+
+        let path = dir.join(name);
+        match entry {
+            Value::Object(_) => validate(&path, entry, overwrite)?,
+            Value::String(_) | Value::Null => {
+                if path.is_dir() {
+                    return Err(format!("{} exists and is a directory", path.display()));
+                }
+                if path.exists() && !overwrite {
+                    return Err(format!("{} already exists", path.display()));
+                }
+            }
+            _ => return Err(format!("Unsupported entry for {:?}: expected object, string or null", name)),
+        }
+    }
+
+    Ok(())
+}
+
+// Creates root and any missing ancestors one level at a time, outermost
+// first, so a rollback removes every directory this call added
+fn create_missing_dirs(root: &Path, changes: &mut Changes) -> Result<(), String> {
+    let mut missing: Vec<&Path> = root.ancestors().take_while(|dir| !dir.exists()).collect();
+    missing.reverse();
+
+    for dir in missing {
+        fs::create_dir(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        changes.created_dirs.push(dir.to_path_buf());
+    }
+    Ok(())
+}
+
+fn create_entries(dir: &Path, spec: &Value, changes: &mut Changes) -> Result<(), String> {
+    for (name, entry) in spec_entries(spec)? {
+        let path = dir.join(name);
+
+        match entry {
+            Value::Object(_) => {
+                if !path.is_dir() {
+                    fs::create_dir(&path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+                    changes.created_dirs.push(path.clone());
+                }
+                create_entries(&path, entry, changes)?;
+            }
+            _ => {
+                let contents = entry.as_str().unwrap_or("");
+                let previous = fs::read(&path).ok();
+                fs::write(&path, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+
+                match previous {
+                    Some(bytes) => changes.overwritten.push((path, bytes)),
+                    None => changes.created_files.push(path),
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn rollback(changes: Changes) {
+    for (path, bytes) in changes.overwritten {
+        let _ = fs::write(path, bytes);
+    }
+    for path in changes.created_files {
+        let _ = fs::remove_file(path);
+    }
+    // Deepest directories were created last, so remove them first
+    for path in changes.created_dirs.into_iter().rev() {
+        let _ = fs::remove_dir(path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn failure_rolls_back_created_ancestors() {
+        let base = std::env::temp_dir().join(format!("create-tree-test-{}", std::process::id()));
+        fs::create_dir_all(&base).unwrap();
+        let root = base.join("a").join("b");
+
+        // Validation doesn't check name length, so the write of the second
+        // file fails after the first has been created
+        let spec = json!({ "ok.txt": "hi", "z".repeat(300): "too long" });
+        let result = tauri::async_runtime::block_on(create_tree(root.to_string_lossy().to_string(), spec, None));
+
+        let leftovers = fs::read_dir(&base).unwrap().count();
+        fs::remove_dir_all(&base).unwrap();
+        assert!(result.is_err());
+        assert_eq!(leftovers, 0);
+    }
+}
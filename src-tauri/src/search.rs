@@ -0,0 +1,148 @@
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+use crate::operations::{self, Operations};
+use crate::{text, walk};
+
+// Files larger than this are skipped unless the caller sets max_file_size;
+// they are usually generated data or minified bundles
+const DEFAULT_MAX_SEARCH_FILE_BYTES: u64 = 4 * 1024 * 1024;
+
+// The search stops collecting once it has this many matches
+const MAX_SEARCH_MATCHES: usize = 10_000;
+
+#[derive(Debug, Serialize)]
+pub struct SearchMatch {
+    pub path: String,
+    // 1-based, with the column counted in characters
+    pub line: usize,
+    pub column: usize,
+    pub text: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SkippedFile {
+    pub path: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SearchResults {
+    pub matches: Vec<SearchMatch>,
+    // Files passed over for being larger than max_file_size
+    pub skipped: Vec<SkippedFile>,
+    pub truncated: bool,
+}
+
+// Finds every line containing query in the text files under root. Binary
+// files are ignored, and files over max_file_size are listed in skipped
+// rather than read.
+#[tauri::command]
+pub async fn search_in_files(
+    operations: tauri::State<'_, Operations>,
+    root: String,
+    query: String,
+    case_sensitive: Option<bool>,
+    max_file_size: Option<u64>,
+    operation_id: Option<String>,
+) -> Result<SearchResults, String> {
+    if query.is_empty() {
+        return Err("Search query can't be empty".to_string());
+    }
+
+    let operation = operations.begin(operation_id);
+    let case_sensitive = case_sensitive.unwrap_or(false);
+    let needle = if case_sensitive { query.clone() } else { fold_case(&query) };
+    let max_file_size = max_file_size.unwrap_or(DEFAULT_MAX_SEARCH_FILE_BYTES);
+    let mut results = SearchResults {
+        matches: Vec::new(),
+        skipped: Vec::new(),
+        truncated: false,
+    };
+
+    walk::walk(Path::new(&root), &mut |path, metadata| {
+        if operation.is_cancelled() {
+            return false;
+        }
+        if !metadata.is_file() {
+            return true;
+        }
+
+        if metadata.len() > max_file_size {
+            results.skipped.push(SkippedFile {
+                path: path.to_string_lossy().to_string(),
+                size: metadata.len(),
+            });
+            return true;
+        }
+
+        let bytes = match fs::read(path) {
+            Ok(bytes) if !text::is_binary(&bytes) => bytes,
+            _ => return true,
+        };
+        let (content, _) = text::decode(&bytes);
+
+        for (index, line) in content.lines().enumerate() {
+            if let Some(column) = match_column(line, &needle, case_sensitive) {
+                if results.matches.len() >= MAX_SEARCH_MATCHES {
+                    results.truncated = true;
+                    return false;
+                }
+                results.matches.push(SearchMatch {
+                    path: path.to_string_lossy().to_string(),
+                    line: index + 1,
+                    column,
+                    text: line.to_string(),
+                });
+            }
+        }
+        true
+    })
+    .map_err(|e| e.to_string())?;
+
+    if operation.is_cancelled() {
+        return Err(operations::CANCELLED.to_string());
+    }
+
+    Ok(results)
+}
+
+// Lowercases one character at a time, so each folded character can be traced
+// back to the one it came from
+fn fold_case(text: &str) -> String {
+    text.chars().flat_map(char::to_lowercase).collect()
+}
+
+// 1-based column, in characters of the original line, where needle first
+// occurs. Lowercasing can change the number of characters ('İ' becomes two),
+// so case-insensitive matches are mapped back to the original line.
+fn match_column(line: &str, needle: &str, case_sensitive: bool) -> Option<usize> {
+    if case_sensitive {
+        return line.find(needle).map(|offset| line[..offset].chars().count() + 1);
+    }
+
+    let mut folded = String::with_capacity(line.len());
+    let mut origins = Vec::with_capacity(line.len());
+    for (index, c) in line.chars().enumerate() {
+        for lower in c.to_lowercase() {
+            folded.push(lower);
+            origins.push(index);
+        }
+    }
+
+    let offset = folded.find(needle)?;
+    Some(origins[folded[..offset].chars().count()] + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn columns_count_characters_of_the_original_line() {
+        assert_eq!(match_column("İstanbul foo", &fold_case("FOO"), false), Some(10));
+        assert_eq!(match_column("ßa Foo", "Foo", true), Some(4));
+        assert_eq!(match_column("abc", "x", false), None);
+    }
+}
@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::Manager;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenFileState {
+    pub path: String,
+    #[serde(default)]
+    pub scroll_top: f64,
+    #[serde(default)]
+    pub cursor_line: usize,
+    #[serde(default)]
+    pub cursor_column: usize,
+    // The tab that was in front
+    #[serde(default)]
+    pub active: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RestoredSession {
+    pub files: Vec<OpenFileState>,
+    // Paths that were in the session but no longer exist
+    pub dropped: Vec<String>,
+}
+
+#[tauri::command]
+pub async fn save_session(app: tauri::AppHandle, files: Vec<OpenFileState>) -> Result<(), String> {
+    let path = session_path(&app)?;
+    crate::file_ops::ensure_parent(&path)?;
+
+    let json = serde_json::to_string_pretty(&files).map_err(|e| e.to_string())?;
+    crate::file_ops::write_atomic(&path, json.as_bytes()).map_err(|e| e.to_string())
+}
+
+// The saved open files, minus any that have since been deleted. If the active
+// tab was one of them, the first remaining file becomes active instead.
+#[tauri::command]
+pub async fn load_session(app: tauri::AppHandle) -> Result<RestoredSession, String> {
+    let bytes = match fs::read(session_path(&app)?) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(RestoredSession {
+                files: Vec::new(),
+                dropped: Vec::new(),
+            })
+        }
+        Err(e) => return Err(e.to_string()),
+    };
+    let saved: Vec<OpenFileState> =
+        serde_json::from_slice(&bytes).map_err(|e| format!("Invalid session file: {}", e))?;
+
+    let (mut files, missing): (Vec<_>, Vec<_>) = saved.into_iter().partition(|file| Path::new(&file.path).is_file());
+    if !files.iter().any(|file| file.active) {
+        if let Some(first) = files.first_mut() {
+            first.active = true;
+        }
+    }
+
+    Ok(RestoredSession {
+        files,
+        dropped: missing.into_iter().map(|file| file.path).collect(),
+    })
+}
+
+fn session_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join("session.json"))
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,178 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::Manager;
+
+use crate::file_ops;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    // Largest share of currently available memory a single full file read may use
+    pub max_read_memory_fraction: f64,
+    // Most recently opened folders, newest first
+    pub recent_folders: Vec<String>,
+    // Terminal program for open_terminal, replacing the platform default
+    pub terminal: Option<String>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            max_read_memory_fraction: 0.25,
+            recent_folders: Vec::new(),
+            terminal: None,
+        }
+    }
+}
+
+impl Settings {
+    fn check(&self) -> Result<(), String> {
+        if !valid_memory_fraction(self.max_read_memory_fraction) {
+            return Err(format!(
+                "max_read_memory_fraction must be above 0 and at most 1, not {}",
+                self.max_read_memory_fraction
+            ));
+        }
+        Ok(())
+    }
+}
+
+// Zero or less would refuse every read, and more than 1 turns the memory
+// check off; NaN fails both comparisons
+fn valid_memory_fraction(fraction: f64) -> bool {
+    fraction > 0.0 && fraction <= 1.0
+}
+
+// The fields update_settings should change; anything left out keeps its
+// current value
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct SettingsPatch {
+    pub max_read_memory_fraction: Option<f64>,
+    pub recent_folders: Option<Vec<String>>,
+    // null clears the terminal, as opposed to leaving the field out
+    #[serde(deserialize_with = "present")]
+    pub terminal: Option<Option<String>>,
+}
+
+impl SettingsPatch {
+    fn apply(self, settings: &mut Settings) {
+        if let Some(fraction) = self.max_read_memory_fraction {
+            settings.max_read_memory_fraction = fraction;
+        }
+        if let Some(recent_folders) = self.recent_folders {
+            settings.recent_folders = recent_folders;
+        }
+        if let Some(terminal) = self.terminal {
+            settings.terminal = terminal;
+        }
+    }
+}
+
+// Only called for fields that are present, so a null becomes Some(None)
+fn present<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+pub struct SettingsState(pub Mutex<Settings>);
+
+impl SettingsState {
+    pub fn get(&self) -> Settings {
+        self.0.lock().unwrap().clone()
+    }
+
+    // Applies a change, checks it and persists the result. The change is made
+    // to a copy that only replaces the current settings once it has been
+    // saved, so a failed save leaves memory and disk in agreement.
+    pub fn update<F>(&self, app: &tauri::AppHandle, change: F) -> Result<(), String>
+    where
+        F: FnOnce(&mut Settings),
+    {
+        let mut settings = self.0.lock().unwrap();
+        let mut updated = settings.clone();
+        change(&mut updated);
+        updated.check()?;
+        save(app, &updated)?;
+        *settings = updated;
+        Ok(())
+    }
+}
+
+#[tauri::command]
+pub async fn get_settings(state: tauri::State<'_, SettingsState>) -> Result<Settings, String> {
+    Ok(state.get())
+}
+
+#[tauri::command]
+pub async fn update_settings(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SettingsState>,
+    settings: SettingsPatch,
+) -> Result<(), String> {
+    state.update(&app, |current| settings.apply(current))
+}
+
+fn save(app: &tauri::AppHandle, settings: &Settings) -> Result<(), String> {
+    let path = settings_path(app)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+
+    // A crash partway through a plain write would leave a truncated file, and
+    // load would then quietly fall back to the defaults
+    let json = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    file_ops::write_atomic(&path, json.as_bytes()).map_err(|e| e.to_string())
+}
+
+// Missing or unreadable settings fall back to the defaults, as does an out of
+// range value edited in by hand
+pub fn load(app: &tauri::AppHandle) -> Settings {
+    let mut settings: Settings = settings_path(app)
+        .ok()
+        .and_then(|path| fs::read(path).ok())
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default();
+
+    if !valid_memory_fraction(settings.max_read_memory_fraction) {
+        settings.max_read_memory_fraction = Settings::default().max_read_memory_fraction;
+    }
+    settings
+}
+
+fn settings_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_config_dir()
+        .map(|dir| dir.join("settings.json"))
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn patch_leaves_missing_fields_alone() {
+        let mut settings = Settings {
+            recent_folders: vec!["/work".to_string()],
+            terminal: Some("kitty".to_string()),
+            ..Settings::default()
+        };
+
+        let patch: SettingsPatch = serde_json::from_str(r#"{ "max_read_memory_fraction": 0.5 }"#).unwrap();
+        patch.apply(&mut settings);
+        assert_eq!(settings.max_read_memory_fraction, 0.5);
+        assert_eq!(settings.recent_folders, ["/work"]);
+        assert_eq!(settings.terminal.as_deref(), Some("kitty"));
+
+        let patch: SettingsPatch = serde_json::from_str(r#"{ "terminal": null }"#).unwrap();
+        patch.apply(&mut settings);
+        assert_eq!(settings.terminal, None);
+        assert_eq!(settings.recent_folders, ["/work"]);
+    }
+}
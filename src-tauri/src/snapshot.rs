@@ -0,0 +1,211 @@
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tauri::Manager;
+
+use crate::walk;
+
+// Snapshots older than this, or beyond the newest MAX_SNAPSHOTS, are pruned
+// whenever a new one is taken
+const MAX_SNAPSHOT_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+const MAX_SNAPSHOTS: usize = 100;
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct SnapshotEntry {
+    is_dir: bool,
+    size: u64,
+    modified: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TreeSnapshot {
+    root: String,
+    entries: BTreeMap<String, SnapshotEntry>,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TreeChange {
+    pub path: String,
+    pub is_dir: bool,
+    pub kind: ChangeKind,
+}
+
+#[tauri::command]
+pub async fn snapshot_tree(app: tauri::AppHandle, root: String) -> Result<String, String> {
+    let snapshot = TreeSnapshot {
+        root: root.clone(),
+        entries: scan(Path::new(&root))?,
+    };
+
+    // The token is just a hash of the root and the current time, which also
+    // names the file the snapshot is stored in
+    let mut hasher = DefaultHasher::new();
+    root.hash(&mut hasher);
+    std::time::SystemTime::now().hash(&mut hasher);
+    let token = format!("{:016x}", hasher.finish());
+
+    let dir = snapshot_dir(&app)?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let json = serde_json::to_vec(&snapshot).map_err(|e| e.to_string())?;
+    fs::write(dir.join(format!("{}.json", token)), json).map_err(|e| e.to_string())?;
+    prune(&dir);
+
+    Ok(token)
+}
+
+#[tauri::command]
+pub async fn diff_tree_snapshot(app: tauri::AppHandle, root: String, token: String) -> Result<Vec<TreeChange>, String> {
+    // The token becomes a file name, so only accept what snapshot_tree produces
+    if token.is_empty() || !token.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("Invalid snapshot token: {}", token));
+    }
+
+    let bytes = fs::read(snapshot_dir(&app)?.join(format!("{}.json", token))).map_err(|e| match e.kind() {
+        io::ErrorKind::NotFound => format!("UnknownSnapshot: snapshot {} doesn't exist or has expired", token),
+        _ => e.to_string(),
+    })?;
+    let snapshot: TreeSnapshot = serde_json::from_slice(&bytes).map_err(|e| e.to_string())?;
+
+    if snapshot.root != root {
+        return Err(format!("Snapshot {} was taken of {}, not {}", token, snapshot.root, root));
+    }
+
+    let current = scan(Path::new(&root))?;
+    Ok(diff(&snapshot.entries, &current))
+}
+
+// A path that changed between file and directory is reported as removed and
+// then added, since it's a different kind of entry now
+fn diff(snapshot: &BTreeMap<String, SnapshotEntry>, current: &BTreeMap<String, SnapshotEntry>) -> Vec<TreeChange> {
+    let mut changes = Vec::new();
+
+    for (path, before) in snapshot {
+        match current.get(path) {
+            Some(after) if after.is_dir != before.is_dir => {
+                changes.push(TreeChange {
+                    path: path.clone(),
+                    is_dir: before.is_dir,
+                    kind: ChangeKind::Removed,
+                });
+                changes.push(TreeChange {
+                    path: path.clone(),
+                    is_dir: after.is_dir,
+                    kind: ChangeKind::Added,
+                });
+            }
+            None => changes.push(TreeChange {
+                path: path.clone(),
+                is_dir: before.is_dir,
+                kind: ChangeKind::Removed,
+            }),
+            // A directory's mtime moves whenever its children change, which is
+            // already reported through the children themselves
+            Some(after) if !after.is_dir && after != before => changes.push(TreeChange {
+                path: path.clone(),
+                is_dir: false,
+                kind: ChangeKind::Modified,
+            }),
+            Some(_) => {}
+        }
+    }
+
+    for (path, after) in current {
+        if !snapshot.contains_key(path) {
+            changes.push(TreeChange {
+                path: path.clone(),
+                is_dir: after.is_dir,
+                kind: ChangeKind::Added,
+            });
+        }
+    }
+
+    // Stable, so a type change keeps its removal ahead of its addition
+    changes.sort_by(|a, b| a.path.cmp(&b.path));
+    changes
+}
+
+fn snapshot_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join("snapshots"))
+        .map_err(|e| e.to_string())
+}
+
+// Best effort: a snapshot that can't be removed is tried again next time
+fn prune(dir: &Path) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    let mut snapshots: Vec<(SystemTime, PathBuf)> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+        .collect();
+
+    // Newest first
+    snapshots.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    let now = SystemTime::now();
+    for (index, (modified, path)) in snapshots.iter().enumerate() {
+        let expired = now.duration_since(*modified).is_ok_and(|age| age > MAX_SNAPSHOT_AGE);
+        if index >= MAX_SNAPSHOTS || expired {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+fn scan(root: &Path) -> Result<BTreeMap<String, SnapshotEntry>, String> {
+    let mut entries = BTreeMap::new();
+
+    walk::walk(root, &mut |path, metadata| {
+        entries.insert(
+            walk::relative_path(root, path),
+            SnapshotEntry {
+                is_dir: metadata.is_dir(),
+                size: if metadata.is_dir() { 0 } else { metadata.len() },
+                modified: crate::modified_secs(metadata),
+            },
+        );
+        true
+    })
+    .map_err(|e| e.to_string())?;
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(is_dir: bool) -> SnapshotEntry {
+        SnapshotEntry { is_dir, size: 0, modified: Some(1) }
+    }
+
+    #[test]
+    fn file_turned_directory_is_removed_then_added() {
+        let before = BTreeMap::from([("a".to_string(), entry(false)), ("b".to_string(), entry(true))]);
+        let after = BTreeMap::from([("a".to_string(), entry(true)), ("b".to_string(), entry(true))]);
+
+        let changes: Vec<(String, bool, ChangeKind)> = diff(&before, &after)
+            .into_iter()
+            .map(|change| (change.path, change.is_dir, change.kind))
+            .collect();
+        assert_eq!(changes, [
+            ("a".to_string(), false, ChangeKind::Removed),
+            ("a".to_string(), true, ChangeKind::Added),
+        ]);
+    }
+}
@@ -0,0 +1,122 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use tauri::Emitter;
+
+use crate::operations::{self, Operations};
+use crate::{language, text, walk};
+
+// Progress is reported every this many files
+const PROGRESS_INTERVAL: usize = 200;
+
+// Files larger than this are almost always generated and are skipped
+const MAX_STATS_FILE_BYTES: u64 = 10 * 1024 * 1024;
+
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct LanguageStats {
+    pub language: String,
+    pub files: usize,
+    pub lines: usize,
+    pub blank: usize,
+    pub comment: usize,
+    pub code: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProjectStats {
+    pub totals: LanguageStats,
+    pub languages: Vec<LanguageStats>,
+}
+
+#[derive(Clone, Serialize)]
+struct StatsProgress {
+    operation_id: Option<String>,
+    files_scanned: usize,
+    partial: Vec<LanguageStats>,
+}
+
+// Line, blank and comment counts per language for every recognised text file
+// under root. Comments are counted by line prefix only, so block comments
+// count as code. Partial results are emitted as "text-stats-progress" events.
+#[tauri::command]
+pub async fn project_text_stats(
+    app: tauri::AppHandle,
+    operations: tauri::State<'_, Operations>,
+    root: String,
+    operation_id: Option<String>,
+) -> Result<ProjectStats, String> {
+    let operation = operations.begin(operation_id.clone());
+    let mut by_language: HashMap<&'static str, LanguageStats> = HashMap::new();
+    let mut files_scanned = 0;
+
+    walk::walk(Path::new(&root), &mut |path, metadata| {
+        if operation.is_cancelled() {
+            return false;
+        }
+        if !metadata.is_file() || metadata.len() > MAX_STATS_FILE_BYTES {
+            return true;
+        }
+
+        if let Some(id) = language::detect_language(path) {
+            if let Ok(bytes) = fs::read(path) {
+                if !text::is_binary(&bytes) {
+                    let stats = by_language.entry(id).or_insert_with(|| LanguageStats {
+                        language: id.to_string(),
+                        ..Default::default()
+                    });
+                    count_lines(&String::from_utf8_lossy(&bytes), language::line_comment_prefixes(id), stats);
+                }
+            }
+        }
+
+        files_scanned += 1;
+        if files_scanned % PROGRESS_INTERVAL == 0 {
+            let _ = app.emit("text-stats-progress", StatsProgress {
+                operation_id: operation_id.clone(),
+                files_scanned,
+                partial: by_language.values().cloned().collect(),
+            });
+        }
+        true
+    })
+    .map_err(|e| e.to_string())?;
+
+    if operation.is_cancelled() {
+        return Err(operations::CANCELLED.to_string());
+    }
+
+    let mut languages: Vec<LanguageStats> = by_language.into_values().collect();
+    languages.sort_by(|a, b| b.lines.cmp(&a.lines).then_with(|| a.language.cmp(&b.language)));
+
+    let mut totals = LanguageStats {
+        language: "total".to_string(),
+        ..Default::default()
+    };
+    for stats in &languages {
+        totals.files += stats.files;
+        totals.lines += stats.lines;
+        totals.blank += stats.blank;
+        totals.comment += stats.comment;
+        totals.code += stats.code;
+    }
+
+    Ok(ProjectStats { totals, languages })
+}
+
+fn count_lines(content: &str, comment_prefixes: &[&str], stats: &mut LanguageStats) {
+    stats.files += 1;
+
+    for line in content.lines() {
+        let line = line.trim();
+        stats.lines += 1;
+
+        if line.is_empty() {
+            stats.blank += 1;
+        } else if comment_prefixes.iter().any(|prefix| line.starts_with(prefix)) {
+            stats.comment += 1;
+        } else {
+            stats.code += 1;
+        }
+    }
+}
@@ -0,0 +1,304 @@
+use serde_json::{Map, Number, Value};
+use std::fs;
+use std::path::Path;
+
+// The key of the single-entry object that stands for a TOML datetime
+const TOML_DATETIME_KEY: &str = "$toml_datetime";
+
+#[derive(Clone, Copy)]
+enum Format {
+    Json,
+    Toml,
+    Yaml,
+}
+
+// Parses a JSON, TOML or YAML file (chosen by extension) into a JSON value.
+// Syntax errors carry the line and column where the parser reports them.
+// TOML datetimes come back as {"$toml_datetime": "1979-05-27"} so that
+// write_structured can restore them.
+#[tauri::command]
+pub async fn read_structured(path: String) -> Result<Value, String> {
+    let format = format_for_path(Path::new(&path))?;
+    let contents = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    let contents = contents.strip_prefix('\u{feff}').unwrap_or(&contents);
+
+    match format {
+        Format::Json => serde_json::from_str(contents).map_err(|e| format!("Parse error: {}", e)),
+        Format::Toml => {
+            let table: toml::Table = toml::from_str(contents).map_err(|e| {
+                let message = e.message().trim_end();
+                match e.span() {
+                    Some(span) => {
+                        let (line, column) = line_and_column(contents, span.start);
+                        format!("Parse error: {} at line {} column {}", message, line, column)
+                    }
+                    None => format!("Parse error: {}", message),
+                }
+            })?;
+            Ok(toml_to_json(toml::Value::Table(table)))
+        }
+        Format::Yaml => {
+            let value: serde_yaml::Value = serde_yaml::from_str(contents).map_err(|e| format!("Parse error: {}", e))?;
+            Ok(yaml_to_json(value))
+        }
+    }
+}
+
+// Serializes value as JSON, TOML or YAML (from the extension unless format is
+// given) and writes it atomically. JSON keeps the existing file's indentation.
+#[tauri::command]
+pub async fn write_structured(path: String, value: Value, format: Option<String>) -> Result<(), String> {
+    let format = match format {
+        Some(name) => parse_format(&name.to_lowercase()).ok_or_else(|| format!("Unsupported format: {}", name))?,
+        None => format_for_path(Path::new(&path))?,
+    };
+
+    let mut contents = match format {
+        Format::Json => {
+            let indent = fs::read_to_string(&path)
+                .ok()
+                .and_then(|existing| json_indent(&existing))
+                .unwrap_or_else(|| "  ".to_string());
+            let mut bytes = Vec::new();
+            let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+            let mut serializer = serde_json::Serializer::with_formatter(&mut bytes, formatter);
+            serde::Serialize::serialize(&value, &mut serializer).map_err(|e| e.to_string())?;
+            String::from_utf8(bytes).map_err(|e| e.to_string())?
+        }
+        Format::Toml => {
+            let table = match json_to_toml(value, "")? {
+                toml::Value::Table(table) => table,
+                _ => return Err("Unsupported: a TOML document must be an object at the top level".to_string()),
+            };
+            toml::to_string_pretty(&table).map_err(|e| e.to_string())?
+        }
+        Format::Yaml => serde_yaml::to_string(&value).map_err(|e| e.to_string())?,
+    };
+    if !contents.ends_with('\n') {
+        contents.push('\n');
+    }
+
+    crate::file_ops::write_atomic(Path::new(&path), contents.as_bytes()).map_err(|e| e.to_string())
+}
+
+fn format_for_path(path: &Path) -> Result<Format, String> {
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    parse_format(&extension)
+        .ok_or_else(|| format!("Unsupported format: {} is not a .json, .toml, .yaml or .yml file", path.display()))
+}
+
+fn parse_format(name: &str) -> Option<Format> {
+    match name {
+        "json" => Some(Format::Json),
+        "toml" => Some(Format::Toml),
+        "yaml" | "yml" => Some(Format::Yaml),
+        _ => None,
+    }
+}
+
+// The whitespace before the first indented line, if the JSON is pretty-printed
+fn json_indent(contents: &str) -> Option<String> {
+    contents
+        .lines()
+        .skip(1)
+        .find(|line| line.starts_with([' ', '\t']))
+        .map(|line| line.chars().take_while(|c| *c == ' ' || *c == '\t').collect())
+}
+
+// 1-based line and column of a byte offset
+fn line_and_column(contents: &str, offset: usize) -> (usize, usize) {
+    let mut end = offset.min(contents.len());
+    while !contents.is_char_boundary(end) {
+        end -= 1;
+    }
+    let before = &contents[..end];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().map(|l| l.chars().count()).unwrap_or(0) + 1;
+    (line, column)
+}
+
+// Floats JSON can't hold (NaN, infinities) are kept as their text form
+fn float_to_json(f: f64) -> Value {
+    Number::from_f64(f)
+        .map(Value::Number)
+        .unwrap_or_else(|| Value::String(f.to_string()))
+}
+
+// Dates and times become {"$toml_datetime": "<text form>"}, which
+// json_to_toml turns back into a datetime rather than a quoted string
+fn toml_to_json(value: toml::Value) -> Value {
+    match value {
+        toml::Value::String(s) => Value::String(s),
+        toml::Value::Integer(i) => Value::Number(i.into()),
+        toml::Value::Float(f) => float_to_json(f),
+        toml::Value::Boolean(b) => Value::Bool(b),
+        toml::Value::Datetime(d) => {
+            Value::Object(Map::from_iter([(TOML_DATETIME_KEY.to_string(), Value::String(d.to_string()))]))
+        }
+        toml::Value::Array(items) => Value::Array(items.into_iter().map(toml_to_json).collect()),
+        toml::Value::Table(table) => Value::Object(table.into_iter().map(|(k, v)| (k, toml_to_json(v))).collect()),
+    }
+}
+
+// JSON values TOML can't represent faithfully are reported, keyed by where
+// they are, rather than written as something that won't read back the same
+fn json_to_toml(value: Value, key: &str) -> Result<toml::Value, String> {
+    let at = if key.is_empty() { "the top level".to_string() } else { key.to_string() };
+
+    match value {
+        Value::Null => Err(format!("Unsupported: TOML has no null value (at {})", at)),
+        Value::Bool(b) => Ok(toml::Value::Boolean(b)),
+        Value::Number(n) => match (n.as_i64(), n.as_f64()) {
+            (Some(i), _) => Ok(toml::Value::Integer(i)),
+            (None, Some(f)) if n.is_f64() => Ok(toml::Value::Float(f)),
+            _ => Err(format!("Unsupported: {} is out of range for a TOML integer (at {})", n, at)),
+        },
+        Value::String(s) => Ok(toml::Value::String(s)),
+        Value::Array(items) => {
+            let items = items
+                .into_iter()
+                .enumerate()
+                .map(|(i, item)| json_to_toml(item, &format!("{}[{}]", key, i)))
+                .collect::<Result<Vec<_>, _>>()?;
+            if let Some(first) = items.first() {
+                if items.iter().any(|item| item.type_str() != first.type_str()) {
+                    return Err(format!("Unsupported: TOML arrays must hold a single type (at {})", at));
+                }
+            }
+            Ok(toml::Value::Array(items))
+        }
+        Value::Object(object) if object.len() == 1 && object.contains_key(TOML_DATETIME_KEY) => {
+            match &object[TOML_DATETIME_KEY] {
+                Value::String(text) => text
+                    .parse()
+                    .map(toml::Value::Datetime)
+                    .map_err(|_| format!("Unsupported: {} is not a TOML datetime (at {})", text, at)),
+                _ => Err(format!("Unsupported: {} must be a string (at {})", TOML_DATETIME_KEY, at)),
+            }
+        }
+        Value::Object(object) => {
+            let mut table = toml::Table::new();
+            for (name, value) in object {
+                let path = if key.is_empty() { name.clone() } else { format!("{}.{}", key, name) };
+                table.insert(name, json_to_toml(value, &path)?);
+            }
+            Ok(toml::Value::Table(table))
+        }
+    }
+}
+
+// YAML allows non-string keys, which are converted to their text form; tags
+// are dropped in favour of the value they wrap
+fn yaml_to_json(value: serde_yaml::Value) -> Value {
+    match value {
+        serde_yaml::Value::Null => Value::Null,
+        serde_yaml::Value::Bool(b) => Value::Bool(b),
+        serde_yaml::Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                Value::Number(i.into())
+            } else if let Some(u) = n.as_u64() {
+                Value::Number(u.into())
+            } else {
+                float_to_json(n.as_f64().unwrap_or(f64::NAN))
+            }
+        }
+        serde_yaml::Value::String(s) => Value::String(s),
+        serde_yaml::Value::Sequence(items) => Value::Array(items.into_iter().map(yaml_to_json).collect()),
+        serde_yaml::Value::Mapping(mapping) => {
+            let mut object = Map::new();
+            for (key, value) in mapping {
+                let key = match yaml_to_json(key) {
+                    Value::String(s) => s,
+                    other => other.to_string(),
+                };
+                object.insert(key, yaml_to_json(value));
+            }
+            Value::Object(object)
+        }
+        serde_yaml::Value::Tagged(tagged) => yaml_to_json(tagged.value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(name: &str, contents: &str) -> String {
+        let dir = std::env::temp_dir().join(format!("structured-test-{}-{}", std::process::id(), name));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name).to_string_lossy().to_string();
+        fs::write(&path, contents).unwrap();
+
+        let result = tauri::async_runtime::block_on(async {
+            let value = read_structured(path.clone()).await?;
+            write_structured(path.clone(), value, None).await
+        });
+        let written = fs::read_to_string(&path).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        result.unwrap();
+        written
+    }
+
+    #[test]
+    fn json_round_trip_keeps_key_order_and_indent() {
+        let json = "{\n    \"name\": \"app\",\n    \"scripts\": {\n        \"start\": \"vite\"\n    },\n    \"dependencies\": {}\n}\n";
+        assert_eq!(round_trip("package.json", json), json);
+    }
+
+    #[test]
+    fn toml_round_trip_keeps_key_order() {
+        let toml = "name = \"app\"\nversion = \"0.1.0\"\n\n[dependencies]\nserde = \"1\"\nanyhow = \"1\"\n";
+        assert_eq!(round_trip("Cargo.toml", toml), toml);
+    }
+
+    #[test]
+    fn toml_round_trip_keeps_datetimes() {
+        let toml = "released = 1979-05-27\nat = 1979-05-27T07:32:00Z\nlabel = \"1979-05-27\"\n";
+        assert_eq!(round_trip("dates.toml", toml), toml);
+    }
+
+    #[test]
+    fn yaml_round_trip_keeps_key_order() {
+        let yaml = "zeta: 1\nalpha:\n- a\n- b\n";
+        assert_eq!(round_trip("config.yaml", yaml), yaml);
+    }
+
+    #[test]
+    fn toml_rejects_values_it_cannot_hold() {
+        let error = json_to_toml(serde_json::json!({ "a": { "b": null } }), "").unwrap_err();
+        assert_eq!(error, "Unsupported: TOML has no null value (at a.b)");
+        let error = json_to_toml(serde_json::json!({ "mixed": [1, "two"] }), "").unwrap_err();
+        assert_eq!(error, "Unsupported: TOML arrays must hold a single type (at mixed)");
+    }
+
+    #[test]
+    fn toml_parse_errors_report_line_and_column() {
+        assert_eq!(line_and_column("a = 1\nb = ", 10), (2, 5));
+    }
+
+    #[test]
+    fn write_uses_the_given_format_and_tab_indent_of_the_existing_file() {
+        let dir = std::env::temp_dir().join(format!("structured-write-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let yaml = dir.join("settings.conf").to_string_lossy().to_string();
+        let json = dir.join("tabs.json").to_string_lossy().to_string();
+        fs::write(&json, "{\n\t\"old\": true\n}").unwrap();
+
+        let value = serde_json::json!({ "b": 1, "a": [true] });
+        let result = tauri::async_runtime::block_on(async {
+            write_structured(yaml.clone(), value.clone(), Some("YAML".to_string())).await?;
+            write_structured(json.clone(), value, None).await
+        });
+        let written_yaml = fs::read_to_string(&yaml).unwrap();
+        let written_json = fs::read_to_string(&json).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        result.unwrap();
+        assert_eq!(written_yaml, "b: 1\na:\n- true\n");
+        assert_eq!(written_json, "{\n\t\"b\": 1,\n\t\"a\": [\n\t\ttrue\n\t]\n}\n");
+    }
+}
@@ -0,0 +1,710 @@
+use serde::Serialize;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use tauri::Emitter;
+
+use crate::operations::{self, Operations};
+use crate::walk;
+
+// How many leading bytes are checked when deciding if a file is binary
+const BINARY_SNIFF_BYTES: usize = 8000;
+
+// How much of a BOM-less file detect_bom_and_encoding looks at
+const ENCODING_SNIFF_BYTES: usize = 64 * 1024;
+
+// read_last_lines reads backwards from the end in blocks of this size
+const TAIL_BLOCK_BYTES: u64 = 64 * 1024;
+
+// Audit reports are streamed to the frontend in batches of this size
+const REPORT_BATCH_SIZE: usize = 100;
+
+pub fn is_binary(bytes: &[u8]) -> bool {
+    bytes.iter().take(BINARY_SNIFF_BYTES).any(|&b| b == 0)
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LineEnding {
+    Lf,
+    Crlf,
+    Mixed,
+    None,
+}
+
+// Counts line endings across any number of chunks, so a CRLF split between
+// two reads is still counted once
+#[derive(Default)]
+pub struct LineEndingCounter {
+    pub lf: usize,
+    pub crlf: usize,
+    last_was_cr: bool,
+}
+
+impl LineEndingCounter {
+    pub fn feed(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            if byte == b'\n' {
+                if self.last_was_cr {
+                    self.crlf += 1;
+                } else {
+                    self.lf += 1;
+                }
+            }
+            self.last_was_cr = byte == b'\r';
+        }
+    }
+
+    pub fn style(&self) -> LineEnding {
+        match (self.lf, self.crlf) {
+            (0, 0) => LineEnding::None,
+            (_, 0) => LineEnding::Lf,
+            (0, _) => LineEnding::Crlf,
+            _ => LineEnding::Mixed,
+        }
+    }
+}
+
+pub fn detect_line_ending(bytes: &[u8]) -> LineEnding {
+    let mut counter = LineEndingCounter::default();
+    counter.feed(bytes);
+    counter.style()
+}
+
+// The dominant style of an existing file, judged from its first 64KB. The
+// head is decoded first, since in UTF-16 or UTF-32 a CRLF isn't two adjacent
+// bytes.
+pub fn predominant_line_ending(path: &Path) -> Option<LineEnding> {
+    let mut head = Vec::new();
+    File::open(path).ok()?.take(64 * 1024).read_to_end(&mut head).ok()?;
+
+    let mut counter = LineEndingCounter::default();
+    counter.feed(decode(&head).0.as_bytes());
+    match counter.style() {
+        LineEnding::Mixed if counter.crlf > counter.lf => Some(LineEnding::Crlf),
+        LineEnding::Mixed => Some(LineEnding::Lf),
+        LineEnding::None => None,
+        style => Some(style),
+    }
+}
+
+pub fn normalize_line_endings(content: &str, ending: LineEnding) -> String {
+    let lf = content.replace("\r\n", "\n");
+    match ending {
+        LineEnding::Crlf => lf.replace('\n', "\r\n"),
+        _ => lf,
+    }
+}
+
+// Decodes file bytes using their BOM if present, then UTF-8, falling back to
+// Latin-1 (which maps every byte) so a file can always be shown
+pub fn decode(bytes: &[u8]) -> (String, &'static str) {
+    if let Some(bom) = detect_bom(bytes) {
+        let rest = &bytes[bom.bytes().len()..];
+        let text = match bom {
+            Bom::Utf8 => String::from_utf8_lossy(rest).to_string(),
+            Bom::Utf16Le => decode_utf16(rest, u16::from_le_bytes),
+            Bom::Utf16Be => decode_utf16(rest, u16::from_be_bytes),
+            Bom::Utf32Le => decode_utf32(rest, u32::from_le_bytes),
+            Bom::Utf32Be => decode_utf32(rest, u32::from_be_bytes),
+        };
+        return (text, bom.encoding());
+    }
+
+    match std::str::from_utf8(bytes) {
+        Ok(text) => (text.to_string(), "utf-8"),
+        Err(_) => (bytes.iter().map(|&b| b as char).collect(), "iso-8859-1"),
+    }
+}
+
+fn decode_utf16(bytes: &[u8], to_unit: fn([u8; 2]) -> u16) -> String {
+    let units: Vec<u16> = bytes.chunks_exact(2).map(|pair| to_unit([pair[0], pair[1]])).collect();
+    String::from_utf16_lossy(&units)
+}
+
+fn decode_utf32(bytes: &[u8], to_scalar: fn([u8; 4]) -> u32) -> String {
+    bytes
+        .chunks_exact(4)
+        .map(|quad| char::from_u32(to_scalar([quad[0], quad[1], quad[2], quad[3]])).unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+pub enum Bom {
+    #[serde(rename = "utf-8")]
+    Utf8,
+    #[serde(rename = "utf-16le")]
+    Utf16Le,
+    #[serde(rename = "utf-16be")]
+    Utf16Be,
+    #[serde(rename = "utf-32le")]
+    Utf32Le,
+    #[serde(rename = "utf-32be")]
+    Utf32Be,
+}
+
+impl Bom {
+    pub fn bytes(self) -> &'static [u8] {
+        match self {
+            Bom::Utf8 => &[0xEF, 0xBB, 0xBF],
+            Bom::Utf16Le => &[0xFF, 0xFE],
+            Bom::Utf16Be => &[0xFE, 0xFF],
+            Bom::Utf32Le => &[0xFF, 0xFE, 0x00, 0x00],
+            Bom::Utf32Be => &[0x00, 0x00, 0xFE, 0xFF],
+        }
+    }
+
+    pub fn encoding(self) -> &'static str {
+        match self {
+            Bom::Utf8 => "utf-8",
+            Bom::Utf16Le => "utf-16le",
+            Bom::Utf16Be => "utf-16be",
+            Bom::Utf32Le => "utf-32le",
+            Bom::Utf32Be => "utf-32be",
+        }
+    }
+
+    // The BOM followed by text in the encoding it marks
+    pub fn encode(self, text: &str) -> Vec<u8> {
+        let mut bytes = self.bytes().to_vec();
+        match self {
+            Bom::Utf8 => bytes.extend_from_slice(text.as_bytes()),
+            Bom::Utf16Le => bytes.extend(text.encode_utf16().flat_map(u16::to_le_bytes)),
+            Bom::Utf16Be => bytes.extend(text.encode_utf16().flat_map(u16::to_be_bytes)),
+            Bom::Utf32Le => bytes.extend(text.chars().flat_map(|c| (c as u32).to_le_bytes())),
+            Bom::Utf32Be => bytes.extend(text.chars().flat_map(|c| (c as u32).to_be_bytes())),
+        }
+        bytes
+    }
+}
+
+// UTF-32LE's BOM starts with UTF-16LE's, so the longer ones are checked first
+pub fn detect_bom(bytes: &[u8]) -> Option<Bom> {
+    [Bom::Utf32Le, Bom::Utf32Be, Bom::Utf8, Bom::Utf16Le, Bom::Utf16Be]
+        .into_iter()
+        .find(|bom| bytes.starts_with(bom.bytes()))
+}
+
+pub fn read_bom(path: &Path) -> Option<Bom> {
+    let mut head = Vec::new();
+    File::open(path).ok()?.take(4).read_to_end(&mut head).ok()?;
+    detect_bom(&head)
+}
+
+#[derive(Debug, Serialize)]
+pub struct EncodingInfo {
+    pub encoding: &'static str,
+    pub bom: Option<Bom>,
+    // "bom" when the BOM decided it, "heuristic" when guessed from content
+    pub detected_by: &'static str,
+}
+
+// Reports the file's BOM, if any, and otherwise guesses the encoding from the
+// first ENCODING_SNIFF_BYTES bytes
+#[tauri::command]
+pub async fn detect_bom_and_encoding(path: String) -> Result<EncodingInfo, String> {
+    let mut head = Vec::new();
+    File::open(&path)
+        .map_err(|e| e.to_string())?
+        .take(ENCODING_SNIFF_BYTES as u64)
+        .read_to_end(&mut head)
+        .map_err(|e| e.to_string())?;
+
+    if let Some(bom) = detect_bom(&head) {
+        return Ok(EncodingInfo {
+            encoding: bom.encoding(),
+            bom: Some(bom),
+            detected_by: "bom",
+        });
+    }
+
+    Ok(EncodingInfo {
+        encoding: guess_encoding(&head),
+        bom: None,
+        detected_by: "heuristic",
+    })
+}
+
+// Text in UTF-16 or UTF-32 without a BOM shows up as zero bytes in fixed
+// positions, since most characters in most text fit in the low byte. Anything
+// else that is valid UTF-8 is taken as UTF-8, and the rest as Latin-1.
+fn guess_encoding(bytes: &[u8]) -> &'static str {
+    if bytes.len() >= 4 {
+        let zeros_at = |lane: usize, width: usize| {
+            let lanes = bytes.chunks_exact(width).count();
+            let zeros = bytes.chunks_exact(width).filter(|unit| unit[lane] == 0).count();
+            lanes > 0 && zeros * 10 >= lanes * 9
+        };
+
+        if zeros_at(1, 4) && zeros_at(2, 4) && zeros_at(3, 4) {
+            return "utf-32le";
+        }
+        if zeros_at(0, 4) && zeros_at(1, 4) && zeros_at(2, 4) {
+            return "utf-32be";
+        }
+        if zeros_at(1, 2) && !zeros_at(0, 2) {
+            return "utf-16le";
+        }
+        if zeros_at(0, 2) && !zeros_at(1, 2) {
+            return "utf-16be";
+        }
+    }
+
+    // The sniffed prefix may end partway through a character
+    let complete = &bytes[..utf8_boundary(bytes)];
+    match std::str::from_utf8(complete) {
+        Ok(_) => "utf-8",
+        Err(_) => "iso-8859-1",
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LineEndingReport {
+    pub path: String,
+    pub style: LineEnding,
+    pub lf_count: usize,
+    pub crlf_count: usize,
+}
+
+#[derive(Clone, Serialize)]
+struct LineEndingProgress {
+    operation_id: Option<String>,
+    reports: Vec<LineEndingReport>,
+}
+
+// Reports every text file with line breaks under root. Partial results are
+// emitted as "line-endings-progress" events while the scan runs.
+#[tauri::command]
+pub async fn audit_line_endings(
+    app: tauri::AppHandle,
+    operations: tauri::State<'_, Operations>,
+    root: String,
+    operation_id: Option<String>,
+) -> Result<Vec<LineEndingReport>, String> {
+    let operation = operations.begin(operation_id.clone());
+    let mut reports = Vec::new();
+    let mut batch = Vec::new();
+
+    walk::walk(Path::new(&root), &mut |path, metadata| {
+        if operation.is_cancelled() {
+            return false;
+        }
+
+        if metadata.is_file() {
+            if let Some(report) = audit_file(path) {
+                batch.push(report);
+            }
+        }
+
+        if batch.len() >= REPORT_BATCH_SIZE {
+            let _ = app.emit("line-endings-progress", LineEndingProgress {
+                operation_id: operation_id.clone(),
+                reports: batch.clone(),
+            });
+            reports.append(&mut batch);
+        }
+        true
+    })
+    .map_err(|e| e.to_string())?;
+
+    if operation.is_cancelled() {
+        return Err(operations::CANCELLED.to_string());
+    }
+
+    if !batch.is_empty() {
+        let _ = app.emit("line-endings-progress", LineEndingProgress {
+            operation_id,
+            reports: batch.clone(),
+        });
+        reports.append(&mut batch);
+    }
+
+    Ok(reports)
+}
+
+fn audit_file(path: &Path) -> Option<LineEndingReport> {
+    let mut file = File::open(path).ok()?;
+    let mut buffer = vec![0u8; 64 * 1024];
+    let mut counter = LineEndingCounter::default();
+    let mut first_chunk = true;
+
+    loop {
+        let read = file.read(&mut buffer).ok()?;
+        if read == 0 {
+            break;
+        }
+        if first_chunk && is_binary(&buffer[..read]) {
+            return None;
+        }
+        first_chunk = false;
+        counter.feed(&buffer[..read]);
+    }
+
+    match counter.style() {
+        LineEnding::None => None,
+        style => Some(LineEndingReport {
+            path: path.to_string_lossy().to_string(),
+            style,
+            lf_count: counter.lf,
+            crlf_count: counter.crlf,
+        }),
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct CappedContent {
+    pub content: String,
+    pub truncated: bool,
+    pub size: u64,
+}
+
+// Reads at most max_bytes for a quick preview; truncated is set when the file
+// is larger than that
+#[tauri::command]
+pub async fn read_file_capped(path: String, max_bytes: usize) -> Result<CappedContent, String> {
+    let file = File::open(&path).map_err(|e| e.to_string())?;
+    let size = file.metadata().map_err(|e| e.to_string())?.len();
+
+    let mut bytes = Vec::with_capacity(max_bytes.min(size as usize));
+    file.take(max_bytes as u64).read_to_end(&mut bytes).map_err(|e| e.to_string())?;
+
+    let truncated = size > bytes.len() as u64;
+    if truncated {
+        bytes.truncate(utf8_boundary(&bytes));
+    }
+
+    Ok(CappedContent {
+        content: String::from_utf8_lossy(&bytes).to_string(),
+        truncated,
+        size,
+    })
+}
+
+// The last n lines of the file, read backwards from the end a block at a time
+// so only as much as needed is read. A final newline doesn't count as an empty
+// last line, and CRLF endings are stripped along with LF.
+#[tauri::command]
+pub async fn read_last_lines(path: String, n: usize) -> Result<Vec<String>, String> {
+    if n == 0 {
+        return Ok(Vec::new());
+    }
+
+    let mut file = File::open(&path).map_err(|e| e.to_string())?;
+    let size = file.metadata().map_err(|e| e.to_string())?.len();
+    let mut tail: Vec<u8> = Vec::new();
+    let mut position = size;
+
+    // n lines need n line breaks before them, plus possibly the final one
+    loop {
+        let breaks = tail.iter().filter(|&&b| b == b'\n').count();
+        let trailing = usize::from(tail.last() == Some(&b'\n'));
+        if position == 0 || breaks >= n + trailing {
+            break;
+        }
+
+        let block = TAIL_BLOCK_BYTES.min(position);
+        position -= block;
+        let mut chunk = vec![0u8; block as usize];
+        file.seek(SeekFrom::Start(position)).map_err(|e| e.to_string())?;
+        file.read_exact(&mut chunk).map_err(|e| e.to_string())?;
+        chunk.extend_from_slice(&tail);
+        tail = chunk;
+    }
+
+    // Cutting just after a line break never splits a UTF-8 character
+    let text = String::from_utf8_lossy(&tail);
+    let text = text.strip_suffix('\n').unwrap_or(&text);
+    let mut lines: Vec<String> = text
+        .split('\n')
+        .map(|line| line.strip_suffix('\r').unwrap_or(line).to_string())
+        .collect();
+    if size == 0 {
+        lines.clear();
+    }
+
+    let skip = lines.len().saturating_sub(n);
+    Ok(lines.split_off(skip))
+}
+
+// Save-time cleanup: strips spaces and tabs from line ends and/or adds a
+// missing final newline (in the file's own line ending style). The file is
+// only rewritten if something changed. Binary and non-UTF-8 files are left
+// alone. Returns whether the file was modified.
+#[tauri::command]
+pub async fn clean_file(path: String, trim_trailing_ws: bool, ensure_final_newline: bool) -> Result<bool, String> {
+    let bytes = std::fs::read(&path).map_err(|e| e.to_string())?;
+    if is_binary(&bytes) {
+        return Ok(false);
+    }
+    let content = match String::from_utf8(bytes) {
+        Ok(content) => content,
+        Err(_) => return Ok(false),
+    };
+
+    let mut cleaned = if trim_trailing_ws {
+        content
+            .split_inclusive('\n')
+            .map(|line| {
+                let (body, ending) = match line.strip_suffix("\r\n") {
+                    Some(body) => (body, "\r\n"),
+                    None => match line.strip_suffix('\n') {
+                        Some(body) => (body, "\n"),
+                        None => (line, ""),
+                    },
+                };
+                format!("{}{}", body.trim_end_matches([' ', '\t']), ending)
+            })
+            .collect()
+    } else {
+        content.clone()
+    };
+
+    if ensure_final_newline && !cleaned.is_empty() && !cleaned.ends_with('\n') {
+        cleaned.push_str(match detect_line_ending(cleaned.as_bytes()) {
+            LineEnding::Crlf => "\r\n",
+            _ => "\n",
+        });
+    }
+
+    if cleaned == content {
+        return Ok(false);
+    }
+    crate::file_ops::write_atomic(Path::new(&path), cleaned.as_bytes()).map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+#[derive(Debug, Serialize)]
+pub struct Records {
+    pub records: Vec<String>,
+    pub has_more: bool,
+}
+
+// Splits the file on delimiter (any string, such as "\0" for find -print0
+// output) and returns up to max_records records. The file is read in blocks,
+// so only what's needed is loaded; text after the last delimiter is a final
+// record of its own.
+#[tauri::command]
+pub async fn read_file_records(path: String, delimiter: String, max_records: usize) -> Result<Records, String> {
+    if delimiter.is_empty() {
+        return Err("Delimiter can't be empty".to_string());
+    }
+    let delimiter = delimiter.as_bytes();
+
+    let mut file = File::open(&path).map_err(|e| e.to_string())?;
+    let mut buffer = vec![0u8; TAIL_BLOCK_BYTES as usize];
+    let mut pending: Vec<u8> = Vec::new();
+    // pending before this offset has already been searched, less the few
+    // bytes a delimiter split across two blocks could start in
+    let mut searched = 0;
+    let mut records = Vec::new();
+    let mut at_end = false;
+
+    while records.len() < max_records {
+        match find_bytes(&pending[searched..], delimiter) {
+            Some(offset) => {
+                let end = searched + offset;
+                records.push(String::from_utf8_lossy(&pending[..end]).to_string());
+                pending.drain(..end + delimiter.len());
+                searched = 0;
+            }
+            None if at_end => {
+                if !pending.is_empty() {
+                    records.push(String::from_utf8_lossy(&pending).to_string());
+                    pending.clear();
+                }
+                break;
+            }
+            None => {
+                searched = pending.len().saturating_sub(delimiter.len() - 1);
+                let read = file.read(&mut buffer).map_err(|e| e.to_string())?;
+                if read == 0 {
+                    at_end = true;
+                }
+                pending.extend_from_slice(&buffer[..read]);
+            }
+        }
+    }
+
+    // Anything left over, buffered or not yet read, is another record
+    let has_more = !pending.is_empty() || (!at_end && file.read(&mut buffer[..1]).map_err(|e| e.to_string())? > 0);
+    Ok(Records { records, has_more })
+}
+
+fn find_bytes(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+// Length of bytes without a trailing, incomplete UTF-8 sequence, so a cut in
+// the middle of a multi-byte character doesn't turn into a replacement char
+pub(crate) fn utf8_boundary(bytes: &[u8]) -> usize {
+    // A sequence is at most 4 bytes, so only the last 3 can start an unfinished one
+    for back in 1..=bytes.len().min(3) {
+        let start = bytes.len() - back;
+        let byte = bytes[start];
+
+        // Continuation bytes look like 0b10xxxxxx; keep looking for the lead byte
+        if byte & 0xC0 == 0x80 {
+            continue;
+        }
+
+        let expected = match byte {
+            b if b >= 0xF0 => 4,
+            b if b >= 0xE0 => 3,
+            b if b >= 0xC0 => 2,
+            _ => 1,
+        };
+        return if back < expected { start } else { bytes.len() };
+    }
+
+    bytes.len()
+}
+
+#[derive(Debug, Serialize)]
+pub struct SafeContent {
+    pub content: String,
+    // Zero-based indices of lines longer than max_line_len, counted in the
+    // file as it is on disk rather than in the wrapped content
+    pub long_lines: Vec<usize>,
+    pub longest_line: usize,
+    pub wrapped: bool,
+}
+
+// Like read_file_content, but flags lines too long for the editor to render
+// comfortably and, when wrap is set, hard-wraps them at max_line_len
+#[tauri::command]
+pub async fn read_file_safe_lines(
+    settings: tauri::State<'_, crate::settings::SettingsState>,
+    path: String,
+    max_line_len: usize,
+    wrap: Option<bool>,
+) -> Result<SafeContent, String> {
+    if max_line_len == 0 {
+        return Err("max_line_len must be greater than zero".to_string());
+    }
+
+    crate::check_fits_in_memory(&path, &settings.get())?;
+    let content = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+
+    let mut long_lines = Vec::new();
+    let mut longest_line = 0;
+    for (index, line) in content.split('\n').enumerate() {
+        let len = line.trim_end_matches('\r').chars().count();
+        longest_line = longest_line.max(len);
+        if len > max_line_len {
+            long_lines.push(index);
+        }
+    }
+
+    let wrapped = wrap.unwrap_or(false) && !long_lines.is_empty();
+    let content = if wrapped {
+        hard_wrap(&content, max_line_len)
+    } else {
+        content
+    };
+
+    Ok(SafeContent {
+        content,
+        long_lines,
+        longest_line,
+        wrapped,
+    })
+}
+
+// Breaks are inserted with the line ending of the line being wrapped, so a
+// CRLF file stays CRLF. A last line without an ending uses the file's
+// predominant style.
+fn hard_wrap(content: &str, width: usize) -> String {
+    let mut counter = LineEndingCounter::default();
+    counter.feed(content.as_bytes());
+    let default_break = if counter.crlf > counter.lf { "\r\n" } else { "\n" };
+
+    let mut wrapped = String::with_capacity(content.len() + content.len() / width * 2);
+    for line in content.split_inclusive('\n') {
+        let (body, ending) = match line.strip_suffix("\r\n") {
+            Some(body) => (body, "\r\n"),
+            None => match line.strip_suffix('\n') {
+                Some(body) => (body, "\n"),
+                None => (line, ""),
+            },
+        };
+        let line_break = if ending.is_empty() { default_break } else { ending };
+
+        for (column, c) in body.chars().enumerate() {
+            if column > 0 && column % width == 0 {
+                wrapped.push_str(line_break);
+            }
+            wrapped.push(c);
+        }
+        wrapped.push_str(ending);
+    }
+
+    wrapped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hard_wrap_uses_the_line_ending_of_the_file() {
+        assert_eq!(hard_wrap("abcdef\r\nab\r\nabcde", 3), "abc\r\ndef\r\nab\r\nabc\r\nde");
+        assert_eq!(hard_wrap("abcd\nabc\n", 2), "ab\ncd\nab\nc\n");
+    }
+
+    fn temp_file(name: &str, contents: &[u8]) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("text-test-{}-{}", std::process::id(), name));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn records_split_across_blocks_are_joined() {
+        // The delimiter straddles the end of the first block
+        let block = TAIL_BLOCK_BYTES as usize;
+        let mut contents = vec![b'a'; block - 1];
+        contents.extend_from_slice(b"<>b<>c");
+        let path = temp_file("records", &contents);
+
+        let first = tauri::async_runtime::block_on(read_file_records(path.to_string_lossy().to_string(), "<>".to_string(), 2));
+        let all = tauri::async_runtime::block_on(read_file_records(path.to_string_lossy().to_string(), "<>".to_string(), 10));
+        std::fs::remove_file(&path).unwrap();
+
+        let first = first.unwrap();
+        assert_eq!(first.records.len(), 2);
+        assert_eq!(first.records[0].len(), block - 1);
+        assert_eq!(first.records[1], "b");
+        assert!(first.has_more);
+
+        let all = all.unwrap();
+        assert_eq!(all.records.len(), 3);
+        assert_eq!(all.records[2], "c");
+        assert!(!all.has_more);
+    }
+
+    #[test]
+    fn last_lines_span_blocks_and_strip_endings() {
+        let long = "x".repeat(TAIL_BLOCK_BYTES as usize);
+        let path = temp_file("tail", format!("first\r\n{}\r\nsecond\r\nthird\r\n", long).as_bytes());
+
+        let lines = tauri::async_runtime::block_on(read_last_lines(path.to_string_lossy().to_string(), 3));
+        let all = tauri::async_runtime::block_on(read_last_lines(path.to_string_lossy().to_string(), 10));
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(lines.unwrap(), [long.clone(), "second".to_string(), "third".to_string()]);
+        assert_eq!(all.unwrap(), ["first".to_string(), long, "second".to_string(), "third".to_string()]);
+    }
+
+    #[test]
+    fn last_lines_of_empty_and_unterminated_files() {
+        let empty = temp_file("tail-empty", b"");
+        let unterminated = temp_file("tail-unterminated", b"a\nb");
+
+        let none = tauri::async_runtime::block_on(read_last_lines(empty.to_string_lossy().to_string(), 2));
+        let last = tauri::async_runtime::block_on(read_last_lines(unterminated.to_string_lossy().to_string(), 1));
+        std::fs::remove_file(&empty).unwrap();
+        std::fs::remove_file(&unterminated).unwrap();
+
+        assert!(none.unwrap().is_empty());
+        assert_eq!(last.unwrap(), ["b"]);
+    }
+}
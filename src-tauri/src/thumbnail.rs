@@ -0,0 +1,200 @@
+use image::ImageFormat;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::any::Any;
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::Manager;
+
+use crate::{file_ops, hashing};
+
+// Cached thumbnails unused for this long, or beyond the most recently used
+// MAX_THUMBNAILS, are pruned after each batch. Edited images get a new cache
+// entry, so the old one is only ever removed this way.
+const MAX_THUMBNAIL_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+const MAX_THUMBNAILS: usize = 5000;
+
+#[derive(Debug, Default, Serialize)]
+pub struct ThumbResult {
+    pub path: String,
+    // The cached PNG, for loading through the asset protocol
+    pub thumbnail: Option<String>,
+    // Size of the original image; None for cache hits, which don't decode it
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub error: Option<String>,
+}
+
+// Thumbnails for a gallery, in the order of paths. Images are decoded on as
+// many threads as there are cores, and one that fails only sets error on its
+// own result. Thumbnails are cached by path, modification time and
+// max_dimension, so reopening a folder reads them straight from the cache.
+#[tauri::command]
+pub async fn generate_thumbnails(
+    app: tauri::AppHandle,
+    paths: Vec<String>,
+    max_dimension: u32,
+) -> Result<Vec<ThumbResult>, String> {
+    if max_dimension == 0 {
+        return Err("max_dimension must be at least 1".to_string());
+    }
+    let cache_dir = thumbnails_dir(&app)?;
+    fs::create_dir_all(&cache_dir).map_err(|e| e.to_string())?;
+
+    let workers = thread::available_parallelism().map_or(1, |n| n.get()).min(paths.len());
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<ThumbResult>> = Mutex::new(paths.iter().map(|_| ThumbResult::default()).collect());
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(path) = paths.get(index) else { break };
+                // A decoder panicking on a corrupt file fails only that file
+                let result = panic::catch_unwind(AssertUnwindSafe(|| thumbnail(&cache_dir, path, max_dimension)))
+                    .unwrap_or_else(|payload| ThumbResult {
+                        path: path.clone(),
+                        error: Some(format!("Could not decode {}: {}", path, panic_message(&*payload))),
+                        ..Default::default()
+                    });
+                results.lock().unwrap()[index] = result;
+            });
+        }
+    });
+
+    prune(&cache_dir);
+    Ok(results.into_inner().unwrap())
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("the decoder panicked")
+}
+
+fn thumbnail(cache_dir: &Path, path: &str, max_dimension: u32) -> ThumbResult {
+    let mut result = ThumbResult {
+        path: path.to_string(),
+        ..Default::default()
+    };
+    match cached_or_generate(cache_dir, path, max_dimension, &mut result) {
+        Ok(thumbnail) => result.thumbnail = Some(thumbnail.to_string_lossy().to_string()),
+        Err(e) => result.error = Some(e),
+    }
+    result
+}
+
+fn cached_or_generate(
+    cache_dir: &Path,
+    path: &str,
+    max_dimension: u32,
+    result: &mut ThumbResult,
+) -> Result<PathBuf, String> {
+    let metadata = fs::metadata(path).map_err(|e| e.to_string())?;
+    if !metadata.is_file() {
+        return Err(format!("{} is not a file", path));
+    }
+    // Nanoseconds, so an edit within the same second still misses the cache
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |duration| duration.as_nanos());
+
+    let key = format!("{}\0{}\0{}", path, modified, max_dimension);
+    let cached = cache_dir.join(format!("{}.png", hashing::to_hex(&Sha256::digest(key.as_bytes()))));
+    if cached.is_file() {
+        // Marks the entry as recently used, so pruning keeps it
+        let _ = fs::File::options()
+            .write(true)
+            .open(&cached)
+            .and_then(|file| file.set_modified(SystemTime::now()));
+        return Ok(cached);
+    }
+
+    let image = image::open(path).map_err(|e| e.to_string())?;
+    result.width = Some(image.width());
+    result.height = Some(image.height());
+
+    // Smaller images are stored as they are rather than scaled up
+    let image = if image.width() > max_dimension || image.height() > max_dimension {
+        image.thumbnail(max_dimension, max_dimension)
+    } else {
+        image
+    };
+    let mut png = Vec::new();
+    image
+        .write_to(&mut std::io::Cursor::new(&mut png), ImageFormat::Png)
+        .map_err(|e| e.to_string())?;
+    file_ops::write_atomic(&cached, &png).map_err(|e| e.to_string())?;
+    Ok(cached)
+}
+
+// Best effort, as in snapshot::prune: an entry that can't be removed is tried
+// again next time
+fn prune(dir: &Path) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    let mut thumbnails: Vec<(SystemTime, PathBuf)> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "png"))
+        .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+        .collect();
+
+    // Most recently used first
+    thumbnails.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    let now = SystemTime::now();
+    for (index, (modified, path)) in thumbnails.iter().enumerate() {
+        let expired = now.duration_since(*modified).is_ok_and(|age| age > MAX_THUMBNAIL_AGE);
+        if index >= MAX_THUMBNAILS || expired {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+fn thumbnails_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_cache_dir()
+        .map(|dir| dir.join("thumbnails"))
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn panic_messages_are_recovered_from_the_payload() {
+        let payload = panic::catch_unwind(|| panic!("bad {} header", "IHDR")).unwrap_err();
+        assert_eq!(panic_message(&*payload), "bad IHDR header");
+        let payload = panic::catch_unwind(|| std::panic::panic_any(7)).unwrap_err();
+        assert_eq!(panic_message(&*payload), "the decoder panicked");
+    }
+
+    #[test]
+    fn prune_removes_expired_thumbnails() {
+        let dir = std::env::temp_dir().join(format!("thumbnail-prune-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("fresh.png"), "").unwrap();
+        let old = fs::File::create(dir.join("old.png")).unwrap();
+        old.set_modified(SystemTime::now() - MAX_THUMBNAIL_AGE - Duration::from_secs(60)).unwrap();
+        drop(old);
+
+        prune(&dir);
+        let fresh = dir.join("fresh.png").exists();
+        let old = dir.join("old.png").exists();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(fresh);
+        assert!(!old);
+    }
+}
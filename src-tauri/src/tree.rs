@@ -0,0 +1,324 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::operations::{self, Operations};
+use crate::walk;
+use crate::FileNode;
+
+// Content matching skips anything larger than this
+const MAX_CONTENT_MATCH_BYTES: u64 = 5 * 1024 * 1024;
+
+// Cap on resolve_glob results when the caller doesn't give one
+const DEFAULT_GLOB_LIMIT: usize = 10_000;
+
+#[derive(Serialize)]
+struct TreeEntry<'a> {
+    path: &'a str,
+    is_dir: bool,
+    size: u64,
+    mtime: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecentFile {
+    pub path: String,
+    pub modified: u64,
+}
+
+// Writes every entry under root to out_path as newline-delimited JSON, one
+// object per line in walk order, and returns how many were written. For trees
+// too big to send over IPC as a FileNode hierarchy.
+#[tauri::command]
+pub async fn export_tree_ndjson(root: String, out_path: String) -> Result<usize, String> {
+    let out = Path::new(&out_path);
+    let mut writer = BufWriter::new(fs::File::create(out).map_err(|e| e.to_string())?);
+    // Compared by identity, not by path, as out_path may be relative or spelled
+    // differently from the paths the walk produces
+    let out_handle = same_file::Handle::from_path(out).map_err(|e| e.to_string())?;
+    let mut count = 0;
+    let mut failure = None;
+
+    walk::walk(Path::new(&root), &mut |path, metadata| {
+        // The export may be written inside the tree it describes
+        if metadata.is_file()
+            && path.file_name() == out.file_name()
+            && same_file::Handle::from_path(path).is_ok_and(|handle| handle == out_handle)
+        {
+            return true;
+        }
+
+        let entry = TreeEntry {
+            path: &path.to_string_lossy(),
+            is_dir: metadata.is_dir(),
+            size: if metadata.is_dir() { 0 } else { metadata.len() },
+            mtime: crate::modified_secs(metadata),
+        };
+        let written = serde_json::to_writer(&mut writer, &entry)
+            .map_err(|e| e.to_string())
+            .and_then(|_| writer.write_all(b"\n").map_err(|e| e.to_string()));
+
+        match written {
+            Ok(()) => count += 1,
+            Err(e) => {
+                failure = Some(e);
+                return false;
+            }
+        }
+        true
+    })
+    .map_err(|e| e.to_string())?;
+
+    if let Some(failure) = failure {
+        return Err(failure);
+    }
+    writer.flush().map_err(|e| e.to_string())?;
+    Ok(count)
+}
+
+#[tauri::command]
+pub async fn find_recent_files(
+    operations: tauri::State<'_, Operations>,
+    root: String,
+    since_secs: u64,
+    limit: usize,
+    operation_id: Option<String>,
+) -> Result<Vec<RecentFile>, String> {
+    let operation = operations.begin(operation_id);
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let cutoff = now.saturating_sub(since_secs);
+
+    let mut files = Vec::new();
+    walk::walk(Path::new(&root), &mut |path, metadata| {
+        if operation.is_cancelled() {
+            return false;
+        }
+
+        if metadata.is_file() {
+            if let Some(modified) = crate::modified_secs(metadata).filter(|&m| m >= cutoff) {
+                files.push(RecentFile {
+                    path: path.to_string_lossy().to_string(),
+                    modified,
+                });
+            }
+        }
+        true
+    })
+    .map_err(|e| e.to_string())?;
+
+    if operation.is_cancelled() {
+        return Err(operations::CANCELLED.to_string());
+    }
+
+    // Most recent first
+    files.sort_by_key(|file| std::cmp::Reverse(file.modified));
+    files.truncate(limit);
+    Ok(files)
+}
+
+#[tauri::command]
+pub async fn filter_tree(root: String, query: String, match_content: bool) -> Result<Vec<FileNode>, String> {
+    let entries = fs::read_dir(&root).map_err(|e| e.to_string())?;
+    Ok(filter_entries(entries, &query.to_lowercase(), match_content))
+}
+
+// Returns only matching files plus the directories needed to reach them.
+// Unreadable entries and subdirectories are skipped, as in walk::walk.
+fn filter_entries(entries: fs::ReadDir, query: &str, match_content: bool) -> Vec<FileNode> {
+    let mut nodes = Vec::new();
+
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+        if walk::is_ignored(&path) {
+            continue;
+        }
+
+        let name = entry.file_name().to_string_lossy().to_string();
+        let file_type = match entry.file_type() {
+            Ok(file_type) => file_type,
+            Err(_) => continue,
+        };
+
+        if file_type.is_dir() {
+            let children = match fs::read_dir(&path) {
+                Ok(children) => filter_entries(children, query, match_content),
+                Err(_) => continue,
+            };
+            if !children.is_empty() {
+                nodes.push(FileNode {
+                    name,
+                    path: path.to_string_lossy().to_string(),
+                    is_dir: true,
+                    children: Some(children),
+                });
+            }
+        } else if name.to_lowercase().contains(query) || (match_content && content_matches(&path, query)) {
+            nodes.push(FileNode {
+                name,
+                path: path.to_string_lossy().to_string(),
+                is_dir: false,
+                children: None,
+            });
+        }
+    }
+
+    crate::sort_nodes(&mut nodes);
+    nodes
+}
+
+fn content_matches(path: &Path, query: &str) -> bool {
+    let small_enough = fs::metadata(path)
+        .map(|m| m.len() <= MAX_CONTENT_MATCH_BYTES)
+        .unwrap_or(false);
+
+    // Binary and non-UTF-8 files fail to read as a string and never match
+    small_enough
+        && fs::read_to_string(path)
+            .map(|content| content.to_lowercase().contains(query))
+            .unwrap_or(false)
+}
+
+// Distinct extensions under root with their file counts, most common first.
+// Files without an extension are counted under "".
+#[tauri::command]
+pub async fn list_extensions(root: String) -> Result<Vec<(String, usize)>, String> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+
+    walk::walk(Path::new(&root), &mut |path, metadata| {
+        if metadata.is_file() {
+            let extension = path
+                .extension()
+                .map(|ext| ext.to_string_lossy().to_lowercase())
+                .unwrap_or_default();
+            *counts.entry(extension).or_insert(0) += 1;
+        }
+        true
+    })
+    .map_err(|e| e.to_string())?;
+
+    let mut extensions: Vec<(String, usize)> = counts.into_iter().collect();
+    extensions.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    Ok(extensions)
+}
+
+#[derive(Debug, Serialize)]
+pub struct GlobMatches {
+    pub paths: Vec<String>,
+    pub truncated: bool,
+}
+
+// Exact glob matching against paths relative to root: "*" and "?" stay within
+// one path component while "**" spans any number of them
+#[tauri::command]
+pub async fn resolve_glob(root: String, pattern: String, limit: Option<usize>) -> Result<GlobMatches, String> {
+    let root = Path::new(&root);
+    let pattern = glob::Pattern::new(&pattern).map_err(|e| format!("Invalid glob pattern: {}", e))?;
+    let options = glob::MatchOptions {
+        case_sensitive: true,
+        require_literal_separator: true,
+        require_literal_leading_dot: false,
+    };
+    let limit = limit.unwrap_or(DEFAULT_GLOB_LIMIT);
+
+    let mut paths = Vec::new();
+    let mut truncated = false;
+    walk::walk(root, &mut |path, _| {
+        if pattern.matches_with(&walk::relative_path(root, path), options) {
+            if paths.len() == limit {
+                truncated = true;
+                return false;
+            }
+            paths.push(path.to_string_lossy().to_string());
+        }
+        true
+    })
+    .map_err(|e| e.to_string())?;
+
+    Ok(GlobMatches { paths, truncated })
+}
+
+// Directories only, for folder pickers. max_depth counts levels below path,
+// so Some(1) returns just the immediate subdirectories.
+#[tauri::command]
+pub async fn read_directories_only(
+    path: String,
+    recursive: bool,
+    max_depth: Option<usize>,
+) -> Result<Vec<FileNode>, String> {
+    let max_depth = if recursive { max_depth.unwrap_or(usize::MAX) } else { 1 };
+    let entries = fs::read_dir(&path).map_err(|e| e.to_string())?;
+    Ok(read_dirs(entries, max_depth))
+}
+
+// Symlinked directories are followed, as in read_directory, so the picker
+// shows the same folders. Unreadable entries and subdirectories are skipped,
+// as in filter_tree.
+fn read_dirs(entries: fs::ReadDir, depth: usize) -> Vec<FileNode> {
+    let mut nodes = Vec::new();
+    if depth == 0 {
+        return nodes;
+    }
+
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+        if !path.is_dir() || walk::is_ignored(&path) {
+            continue;
+        }
+
+        let children = if depth > 1 {
+            match fs::read_dir(&path) {
+                Ok(children) => Some(read_dirs(children, depth - 1)),
+                Err(_) => continue,
+            }
+        } else {
+            None
+        };
+        nodes.push(FileNode {
+            name: entry.file_name().to_string_lossy().to_string(),
+            children,
+            path: path.to_string_lossy().to_string(),
+            is_dir: true,
+        });
+    }
+
+    crate::sort_nodes(&mut nodes);
+    nodes
+}
+
+// Patterns from the root's .treeignore, which read_directory applies when
+// asked to with apply_ignore_file
+#[tauri::command]
+pub async fn read_ignore_rules(root: String) -> Result<Vec<String>, String> {
+    walk::read_ignore_file(Path::new(&root)).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn export_skips_itself_when_given_a_relative_path() {
+        // Relative to the crate directory, where tests run
+        let root = Path::new("target").join(format!("export-tree-test-{}", std::process::id()));
+        fs::create_dir_all(root.join("sub")).unwrap();
+        fs::write(root.join("sub").join("a.txt"), "a").unwrap();
+
+        let out_path = root.join("sub").join("..").join(".").join("tree.ndjson");
+        let count = tauri::async_runtime::block_on(export_tree_ndjson(
+            root.to_string_lossy().to_string(),
+            out_path.to_string_lossy().to_string(),
+        ))
+        .unwrap();
+
+        let output = fs::read_to_string(root.join("tree.ndjson")).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(output.lines().count(), 2);
+        assert!(!output.contains("tree.ndjson"));
+    }
+}
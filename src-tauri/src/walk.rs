@@ -0,0 +1,164 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// Directories that are never worth showing or scanning
+const IGNORED_NAMES: &[&str] = &["node_modules", "target", "__pycache__"];
+
+pub fn is_ignored(path: &Path) -> bool {
+    let ignored_name = path
+        .file_name()
+        .map(|name| IGNORED_NAMES.contains(&name.to_string_lossy().as_ref()))
+        .unwrap_or(false);
+
+    ignored_name || is_hidden(path)
+}
+
+fn is_dotfile(path: &Path) -> bool {
+    path.file_name()
+        .map(|name| name.to_string_lossy().starts_with('.'))
+        .unwrap_or(false)
+}
+
+#[cfg(not(windows))]
+pub fn is_hidden(path: &Path) -> bool {
+    is_dotfile(path)
+}
+
+// Windows hides files with an attribute rather than a name prefix. Dotfiles
+// are still treated as hidden there since that's what dev tooling expects.
+#[cfg(windows)]
+pub fn is_hidden(path: &Path) -> bool {
+    use std::os::windows::fs::MetadataExt;
+    use windows_sys::Win32::Storage::FileSystem::FILE_ATTRIBUTE_HIDDEN;
+
+    let hidden_attribute = fs::symlink_metadata(path)
+        .map(|m| m.file_attributes() & FILE_ATTRIBUTE_HIDDEN != 0)
+        .unwrap_or(false);
+
+    hidden_attribute || is_dotfile(path)
+}
+
+// Project-specific hiding rules from a .treeignore file at the root, using
+// the same glob syntax as resolve_glob. A pattern containing "/" is matched
+// against the path relative to the root, anything else against the entry's
+// name at any depth, and a trailing "/" restricts it to directories.
+pub const IGNORE_FILE_NAME: &str = ".treeignore";
+
+struct IgnorePattern {
+    pattern: glob::Pattern,
+    anchored: bool,
+    dir_only: bool,
+}
+
+pub struct IgnoreRules {
+    patterns: Vec<IgnorePattern>,
+}
+
+impl IgnoreRules {
+    pub fn load(root: &Path) -> Result<Self, io::Error> {
+        let mut patterns = Vec::new();
+
+        for line in read_ignore_file(root)? {
+            let dir_only = line.ends_with('/');
+            let line = line.trim_end_matches('/');
+            let anchored = line.contains('/');
+
+            // Invalid patterns are skipped rather than hiding nothing at all
+            if let Ok(pattern) = glob::Pattern::new(line.trim_start_matches('/')) {
+                patterns.push(IgnorePattern { pattern, anchored, dir_only });
+            }
+        }
+
+        Ok(Self { patterns })
+    }
+
+    pub fn matches(&self, root: &Path, path: &Path, is_dir: bool) -> bool {
+        let relative = relative_path(root, path);
+        let name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+        let options = glob::MatchOptions {
+            case_sensitive: true,
+            require_literal_separator: true,
+            require_literal_leading_dot: false,
+        };
+
+        self.patterns.iter().any(|rule| {
+            let target = if rule.anchored { relative.as_str() } else { name.as_ref() };
+            (is_dir || !rule.dir_only) && rule.pattern.matches_with(target, options)
+        })
+    }
+}
+
+// Non-empty, non-comment lines of the root's .treeignore; a missing file
+// simply means no rules
+pub fn read_ignore_file(root: &Path) -> Result<Vec<String>, io::Error> {
+    let contents = match fs::read_to_string(root.join(IGNORE_FILE_NAME)) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+
+    Ok(contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect())
+}
+
+// Visits every entry below `root` (directories before their contents), skipping
+// ignored entries. The visitor returns false to stop the walk early.
+pub fn walk<F>(root: &Path, visit: &mut F) -> Result<(), io::Error>
+where
+    F: FnMut(&Path, &fs::Metadata) -> bool,
+{
+    let entries = fs::read_dir(root)?;
+    walk_entries(entries, visit);
+    Ok(())
+}
+
+fn walk_entries<F>(entries: fs::ReadDir, visit: &mut F) -> bool
+where
+    F: FnMut(&Path, &fs::Metadata) -> bool,
+{
+    let mut entries: Vec<fs::DirEntry> = entries.filter_map(|entry| entry.ok()).collect();
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let path = entry.path();
+        if is_ignored(&path) {
+            continue;
+        }
+
+        // Use the entry's own metadata so symlinked directories aren't followed
+        let metadata = match entry.metadata() {
+            Ok(metadata) => metadata,
+            Err(_) => continue,
+        };
+
+        if !visit(&path, &metadata) {
+            return false;
+        }
+
+        // Unreadable subdirectories are skipped rather than failing the whole walk
+        if metadata.is_dir() {
+            if let Ok(children) = fs::read_dir(&path) {
+                if !walk_entries(children, visit) {
+                    return false;
+                }
+            }
+        }
+    }
+
+    true
+}
+
+// Path relative to the walk root, always using forward slashes
+pub fn relative_path(root: &Path, path: &Path) -> String {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
@@ -0,0 +1,252 @@
+use notify::event::{ModifyKind, RenameMode};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::Emitter;
+
+// A new file counts as settled once its size stops changing between polls
+const SETTLE_POLL_INTERVAL: Duration = Duration::from_millis(500);
+// Files still growing after this long are assumed to be streams and skipped
+const SETTLE_TIMEOUT: Duration = Duration::from_secs(120);
+
+static NEXT_WATCH_ID: AtomicU64 = AtomicU64::new(1);
+
+#[derive(Default)]
+pub struct PatternWatches(Mutex<HashMap<String, RecommendedWatcher>>);
+
+// One watcher per watch_paths session, covering all of its roots
+#[derive(Default)]
+pub struct WatcherManager(Mutex<HashMap<String, RecommendedWatcher>>);
+
+#[derive(Clone, Serialize)]
+struct PatternMatch {
+    watch_id: String,
+    path: String,
+}
+
+// Emits "pattern-match" for each new file in dir whose name matches the glob,
+// once the file has finished being written. Returns an id for unwatch_pattern.
+#[tauri::command]
+pub async fn watch_for_pattern(
+    app: tauri::AppHandle,
+    watches: tauri::State<'_, PatternWatches>,
+    dir: String,
+    glob: String,
+) -> Result<String, String> {
+    let pattern = glob::Pattern::new(&glob).map_err(|e| format!("Invalid glob pattern: {}", e))?;
+    let watch_id = format!("pattern-{}", NEXT_WATCH_ID.fetch_add(1, Ordering::Relaxed));
+    let pending = Arc::new(Mutex::new(HashSet::new()));
+
+    let event_watch_id = watch_id.clone();
+    let mut watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+        let event = match result {
+            Ok(event) => event,
+            Err(_) => return,
+        };
+
+        // Files written under a temporary name and renamed into place show up
+        // as a rename, which is still a new file as far as the user is concerned
+        let is_new_file = matches!(
+            event.kind,
+            EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(RenameMode::To))
+        );
+        if !is_new_file {
+            return;
+        }
+
+        for path in event.paths {
+            let matches = path
+                .file_name()
+                .map(|name| pattern.matches(&name.to_string_lossy()))
+                .unwrap_or(false);
+
+            if matches && pending.lock().unwrap().insert(path.clone()) {
+                let app = app.clone();
+                let pending = pending.clone();
+                let watch_id = event_watch_id.clone();
+
+                std::thread::spawn(move || {
+                    if wait_until_settled(&path) {
+                        let _ = app.emit("pattern-match", PatternMatch {
+                            watch_id,
+                            path: path.to_string_lossy().to_string(),
+                        });
+                    }
+                    pending.lock().unwrap().remove(&path);
+                });
+            }
+        }
+    })
+    .map_err(|e| e.to_string())?;
+
+    watcher
+        .watch(Path::new(&dir), RecursiveMode::NonRecursive)
+        .map_err(|e| e.to_string())?;
+
+    watches.0.lock().unwrap().insert(watch_id.clone(), watcher);
+    Ok(watch_id)
+}
+
+#[tauri::command]
+pub async fn unwatch_pattern(
+    watches: tauri::State<'_, PatternWatches>,
+    watch_id: String,
+) -> Result<bool, String> {
+    // Dropping the watcher stops it
+    Ok(watches.0.lock().unwrap().remove(&watch_id).is_some())
+}
+
+#[derive(Clone, Serialize)]
+struct FsChange {
+    session_id: String,
+    root: String,
+    kind: &'static str,
+    paths: Vec<String>,
+}
+
+// Watches every root under one session, emitting "fs-change" tagged with the
+// root each change belongs to. Roots nested inside another root are covered
+// by the outer watch rather than watched twice.
+#[tauri::command]
+pub async fn watch_paths(
+    app: tauri::AppHandle,
+    manager: tauri::State<'_, WatcherManager>,
+    paths: Vec<String>,
+) -> Result<String, String> {
+    if paths.is_empty() {
+        return Err("No paths to watch".to_string());
+    }
+
+    // Events may report canonical paths, so roots are matched in that form
+    let mut roots = Vec::new();
+    for path in paths {
+        let canonical = std::fs::canonicalize(&path).map_err(|e| format!("{}: {}", path, e))?;
+        if !roots.iter().any(|(_, existing)| existing == &canonical) {
+            roots.push((path, canonical));
+        }
+    }
+
+    let session_id = format!("session-{}", NEXT_WATCH_ID.fetch_add(1, Ordering::Relaxed));
+    let event_session_id = session_id.clone();
+    let event_roots = roots.clone();
+    let mut watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+        let event = match result {
+            Ok(event) => event,
+            Err(_) => return,
+        };
+        let kind = match event.kind {
+            EventKind::Create(_) => "create",
+            EventKind::Remove(_) => "remove",
+            EventKind::Modify(ModifyKind::Name(_)) => "rename",
+            EventKind::Modify(_) => "modify",
+            _ => return,
+        };
+
+        // Group the paths by root so a rename across roots reaches both
+        let mut by_root: Vec<(&str, Vec<String>)> = Vec::new();
+        for path in &event.paths {
+            if let Some(root) = owning_root(&event_roots, path) {
+                let path = path.to_string_lossy().to_string();
+                match by_root.iter_mut().find(|(r, _)| *r == root) {
+                    Some((_, paths)) => paths.push(path),
+                    None => by_root.push((root, vec![path])),
+                }
+            }
+        }
+
+        for (root, paths) in by_root {
+            let _ = app.emit("fs-change", FsChange {
+                session_id: event_session_id.clone(),
+                root: root.to_string(),
+                kind,
+                paths,
+            });
+        }
+    })
+    .map_err(|e| e.to_string())?;
+
+    for canonical in outermost_roots(roots.iter().map(|(_, canonical)| canonical.clone()).collect()) {
+        watcher
+            .watch(&canonical, RecursiveMode::Recursive)
+            .map_err(|e| format!("{}: {}", canonical.display(), e))?;
+    }
+
+    manager.0.lock().unwrap().insert(session_id.clone(), watcher);
+    Ok(session_id)
+}
+
+#[tauri::command]
+pub async fn unwatch_session(manager: tauri::State<'_, WatcherManager>, session_id: String) -> Result<bool, String> {
+    Ok(manager.0.lock().unwrap().remove(&session_id).is_some())
+}
+
+// The fewest directories whose recursive watches cover every path: files are
+// replaced by their directory and anything inside another entry is dropped.
+// Collapsing further into a shared ancestor would only add watches, since a
+// recursive watch costs one per directory beneath it.
+#[tauri::command]
+pub async fn minimal_watch_roots(paths: Vec<String>) -> Vec<String> {
+    let dirs = paths
+        .iter()
+        .map(|path| {
+            let path = std::fs::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path));
+            match path.parent() {
+                Some(parent) if path.is_file() => parent.to_path_buf(),
+                _ => path,
+            }
+        })
+        .collect();
+
+    outermost_roots(dirs)
+        .into_iter()
+        .map(|dir| dir.to_string_lossy().to_string())
+        .collect()
+}
+
+// Drops any root that sits inside another, since recursive watches already cover it
+fn outermost_roots(mut roots: Vec<PathBuf>) -> Vec<PathBuf> {
+    roots.sort();
+    let mut outermost: Vec<PathBuf> = Vec::new();
+    for root in roots {
+        if !outermost.iter().any(|outer| root.starts_with(outer)) {
+            outermost.push(root);
+        }
+    }
+    outermost
+}
+
+// The most specific requested root containing path, so changes in a nested
+// root are tagged with it rather than the root that contains it
+fn owning_root<'a>(roots: &'a [(String, PathBuf)], path: &Path) -> Option<&'a str> {
+    roots
+        .iter()
+        .filter(|(_, canonical)| path.starts_with(canonical))
+        .max_by_key(|(_, canonical)| canonical.components().count())
+        .map(|(root, _)| root.as_str())
+}
+
+fn wait_until_settled(path: &Path) -> bool {
+    let started = Instant::now();
+    let mut last_size = None;
+
+    while started.elapsed() < SETTLE_TIMEOUT {
+        std::thread::sleep(SETTLE_POLL_INTERVAL);
+
+        let size = match std::fs::metadata(path) {
+            Ok(metadata) if metadata.is_file() => metadata.len(),
+            // Directories and files removed before settling never fire
+            _ => return false,
+        };
+
+        if last_size == Some(size) {
+            return true;
+        }
+        last_size = Some(size);
+    }
+
+    false
+}